@@ -19,15 +19,27 @@ async fn main() {
 
     let client = Inngest::new("rust-dev");
     let mut inngest_handler = Handler::new(&client);
-    inngest_handler.register_fns(vec![
-        dummy_fn(&client).into(),
-        hello_fn(&client).into(),
-        step_run(&client).into(),
-        fallible_step_run(&client).into(),
-        incorrectly_propagates_error(&client).into(),
-    ]);
+    inngest_handler
+        .register_fns(vec![
+            dummy_fn(&client).into(),
+            hello_fn(&client).into(),
+            step_run(&client).into(),
+            fallible_step_run(&client).into(),
+            incorrectly_propagates_error(&client).into(),
+        ])
+        .expect("function IDs should be unique");
 
     let inngest_state = Arc::new(inngest_handler);
+    if let Ok(public_url) = env::var("APP_PUBLIC_URL") {
+        let sync = inngest_state.sync_on_start(&public_url);
+        let handler = Arc::clone(&inngest_state);
+        tokio::spawn(async move {
+            // The handle resolves once the sync succeeds or gives up; the
+            // status is what a health check would report.
+            let _ = sync.await;
+            println!("Inngest sync status: {:?}", handler.sync_status());
+        });
+    }
 
     let app = Router::new()
         .route("/", get(|| async { "OK!\n" }))
@@ -149,7 +161,7 @@ fn hello_fn(client: &Inngest) -> ServableFn<HelloEventData, Error> {
             let step_res = into_dev_result!(
                 step.run("fallible-step-function", || async move {
                     // if even, fail
-                    if input.ctx.attempt % 2 == 0 {
+                    if input.ctx.attempt.is_multiple_of(2) {
                         return Err(UserLandError::General(format!(
                             "Attempt {}",
                             input.ctx.attempt
@@ -239,7 +251,7 @@ fn fallible_step_run(client: &Inngest) -> ServableFn<StepRunEventData, Error> {
             let step_res = into_dev_result!(
                 step.run("fallible-step-function", || async move {
                     // if even, fail
-                    if input.ctx.attempt % 2 == 0 {
+                    if input.ctx.attempt.is_multiple_of(2) {
                         return Err(UserLandError::General(format!(
                             "Attempt {}",
                             input.ctx.attempt
@@ -256,6 +268,7 @@ fn fallible_step_run(client: &Inngest) -> ServableFn<StepRunEventData, Error> {
                     DevError::NoRetry(_) => println!("No retry"),
                     DevError::RetryAt(_) => println!("Retry after"),
                     DevError::Step(err) => println!("Step {}", err),
+                    DevError::Invoke(err) => println!("Invoke {}", err),
                     DevError::Basic(msg) => println!("Basic {}", msg),
                 },
                 Ok(_) => println!("Success"),
@@ -1,20 +1,30 @@
-use serde_json::Value;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
 use slug::slugify;
-use std::future::Future;
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 use url::Url;
 
 use crate::{
-    config::Config,
+    config::{ConfigKey, InngestConfig},
     event::{Event, InngestEvent},
-    function::{FunctionOpts, Input, ServableFn, Trigger},
+    function::{FunctionOpts, InngestFunction, Input, IntoTriggers, ServableFn},
     handler::Kind,
-    result::DevError,
+    header,
+    result::{ClientError, StepError},
+    run::{Cancellation, RunStatus, Runs},
+    signature::Signature,
     step_tool::Step as StepTool,
+    version::{self, EXECUTION_VERSION},
 };
 
 const API_ORIGIN_DEV: &str = "http://127.0.0.1:8288";
 pub(crate) const EVENT_API_ORIGIN: &str = "https://inn.gs";
 pub(crate) const API_ORIGIN: &str = "https://api.inngest.com";
+const FUNCTION_INVOKED_EVENT: &str = "inngest/function.invoked";
 
 /// The response returned by the Inngest event ingestion API.
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize, PartialEq, Eq)]
@@ -34,29 +44,47 @@ pub struct Inngest {
     pub(crate) event_key: Option<String>,
     pub(crate) env: Option<String>,
     pub(crate) dev: Option<String>,
+    pub(crate) signing_key: Option<String>,
+    pub(crate) signing_key_fallback: Option<String>,
     http: reqwest::Client,
+    config: InngestConfig,
 }
 
 impl Inngest {
     pub fn new(id: &str) -> Self {
-        // initialize variable values here using environment variables
-        let api_origin = Config::api_origin();
-        let event_api_origin = Config::event_api_origin();
-        let event_key = Config::event_key();
-        let env = Config::env();
-        let dev = Config::dev().and_then(|v| Self::normalize_dev_value(&v));
+        Self::from_config(id, InngestConfig::from_env())
+    }
+
+    /// Creates a client from a layered [`InngestConfig`], for example one
+    /// loaded with [`InngestConfig::load`].
+    ///
+    /// Builder methods called on the client afterwards still take
+    /// precedence over the configuration.
+    pub fn from_config(id: &str, config: InngestConfig) -> Self {
+        let value = |key| config.get(key).map(|v: &str| v.to_string());
 
         Inngest {
             id: id.to_string(),
-            api_origin,
-            event_api_origin,
-            event_key,
-            env,
-            dev,
+            api_origin: value(ConfigKey::ApiOrigin),
+            event_api_origin: value(ConfigKey::EventApiOrigin),
+            event_key: value(ConfigKey::EventKey),
+            env: value(ConfigKey::Env),
+            dev: config
+                .get(ConfigKey::Dev)
+                .and_then(Self::normalize_dev_value),
+            signing_key: value(ConfigKey::SigningKey),
+            signing_key_fallback: value(ConfigKey::SigningKeyFallback),
             http: reqwest::Client::new(),
+            config,
         }
     }
 
+    /// Returns the configuration the client was created from, including
+    /// where each value came from.
+    pub fn config(&self) -> &InngestConfig {
+        &self.config
+    }
+
     pub fn app_id(&self) -> String {
         slugify(self.id.clone())
     }
@@ -76,6 +104,16 @@ impl Inngest {
         self
     }
 
+    /// Overrides the HTTP client used for every request the SDK makes.
+    ///
+    /// The client is shared by event sends, REST API calls, app syncs and
+    /// run payload hydration, so proxies, custom root certificates, timeouts
+    /// and connection pooling configured on it apply everywhere.
+    pub fn http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
     pub fn env(mut self, e: &str) -> Self {
         self.env = Some(e.to_string());
         self
@@ -86,6 +124,41 @@ impl Inngest {
         self
     }
 
+    /// Overrides the signing key used to authenticate REST API requests.
+    pub fn signing_key(mut self, key: &str) -> Self {
+        self.signing_key = Some(key.to_string());
+        self
+    }
+
+    /// Overrides the fallback signing key used after primary-key auth fails.
+    pub fn signing_key_fallback(mut self, key: &str) -> Self {
+        self.signing_key_fallback = Some(key.to_string());
+        self
+    }
+
+    /// Returns a handle for looking up and controlling function runs through
+    /// the Inngest REST API.
+    pub fn runs(&self) -> Runs<'_> {
+        Runs::new(self)
+    }
+
+    /// Cancels every run of `function_id` that started within the given time
+    /// range and matches the optional `if_expr` expression.
+    ///
+    /// The cancellation is processed asynchronously; see [`Cancellation`]
+    /// for following its progress.
+    pub async fn cancel_runs(
+        &self,
+        function_id: &str,
+        started_after: Option<SystemTime>,
+        started_before: SystemTime,
+        if_expr: Option<&str>,
+    ) -> Result<Cancellation, ClientError> {
+        self.runs()
+            .cancel_bulk(function_id, started_after, started_before, if_expr)
+            .await
+    }
+
     pub(crate) fn mode(&self) -> Kind {
         if self.dev.is_some() {
             Kind::Dev
@@ -94,16 +167,21 @@ impl Inngest {
         }
     }
 
+    /// Creates a function from an async closure.
+    ///
+    /// The closure may return any serializable output `O`; it is serialized
+    /// to JSON as the run's result.
     pub fn create_function<
         T: 'static,
         E,
-        F: Future<Output = Result<Value, E>> + Send + Sync + 'static,
+        O: Serialize + 'static,
+        F: Future<Output = Result<O, E>> + Send + Sync + 'static,
     >(
         &self,
         opts: FunctionOpts,
-        trigger: Trigger,
+        triggers: impl IntoTriggers,
         func: impl Fn(Input<T>, StepTool) -> F + Send + Sync + 'static,
-    ) -> ServableFn<T, E> {
+    ) -> ServableFn<T, E, O> {
         use futures::future::FutureExt;
 
         let app_id = self.app_id();
@@ -111,9 +189,45 @@ impl Inngest {
             app_id,
             client: self.clone(),
             opts,
-            trigger,
+            triggers: triggers.into_triggers(),
             func: Box::new(move |input, step| func(input, step).boxed()),
             on_failure: None,
+            on_cancel: None,
+            on_finish: None,
+            on_success: None,
+            event_names: None,
+        }
+    }
+
+    /// Creates a function from an [`InngestFunction`] implementation.
+    ///
+    /// [`Handler::register_fn`](crate::handler::Handler::register_fn) accepts
+    /// implementations directly; this is only needed to configure the
+    /// returned [`ServableFn`] further, such as adding an `on_failure` handler.
+    pub fn function<F: InngestFunction>(
+        &self,
+        func: F,
+    ) -> ServableFn<F::Event, F::Error, F::Output> {
+        use futures::future::FutureExt;
+
+        let opts = func.opts();
+        let triggers = func.trigger().into_triggers();
+        let func = Arc::new(func);
+
+        ServableFn {
+            app_id: self.app_id(),
+            client: self.clone(),
+            opts,
+            triggers,
+            func: Box::new(move |input, step| {
+                let func = Arc::clone(&func);
+                async move { func.run(input, step).await }.boxed()
+            }),
+            on_failure: None,
+            on_cancel: None,
+            on_finish: None,
+            on_success: None,
+            event_names: None,
         }
     }
 
@@ -121,7 +235,8 @@ impl Inngest {
     pub async fn send_event<T: InngestEvent>(
         &self,
         evt: &Event<T>,
-    ) -> Result<SendEventResponse, DevError> {
+    ) -> Result<SendEventResponse, ClientError> {
+        Self::validate_events([evt]).map_err(ClientError::InvalidRequest)?;
         self.send_payload(evt).await
     }
 
@@ -129,49 +244,213 @@ impl Inngest {
     pub async fn send_events<T: InngestEvent>(
         &self,
         evts: &[&Event<T>],
-    ) -> Result<SendEventResponse, DevError> {
+    ) -> Result<SendEventResponse, ClientError> {
+        Self::validate_events(evts.iter().copied()).map_err(ClientError::InvalidRequest)?;
         self.send_payload(evts).await
     }
 
+    /// Invokes a function from outside of a function run and waits for its
+    /// output.
+    ///
+    /// `function_id` is the fully qualified ID returned by
+    /// [`ServableFn::slug`]. The function is triggered with an
+    /// `inngest/function.invoked` event and the run is polled through the REST
+    /// API until it finishes or `timeout` elapses. A failed run is returned as
+    /// [`ClientError::RunFailed`] carrying the function's error.
+    pub async fn invoke<O: DeserializeOwned>(
+        &self,
+        function_id: &str,
+        data: impl Serialize,
+        timeout: Duration,
+    ) -> Result<O, ClientError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        let mut data = serde_json::to_value(data).map_err(|err| {
+            ClientError::InvalidRequest(format!("error serializing invoke data: {}", err))
+        })?;
+        if data.is_null() {
+            data = json!({});
+        }
+        let Some(fields) = data.as_object_mut() else {
+            return Err(ClientError::InvalidRequest(
+                "invoke data must serialize to a JSON object".to_string(),
+            ));
+        };
+        fields.insert("_inngest".to_string(), json!({ "fn_id": function_id }));
+
+        let response = self
+            .send_payload(&[Event::new(FUNCTION_INVOKED_EVENT, data)])
+            .await?;
+        let Some(event_id) = response.ids.first() else {
+            return Err(ClientError::Api {
+                status: response.status,
+                body: serde_json::to_string(&response).unwrap_or_default(),
+                message: Some("event API did not return an ID for the invoke event".to_string()),
+            });
+        };
+
+        let runs = self.runs();
+        let run = runs
+            .wait_for_event_run(
+                event_id,
+                deadline.saturating_duration_since(tokio::time::Instant::now()),
+            )
+            .await?;
+        let run = runs
+            .wait_for_completion(
+                &run.run_id,
+                deadline.saturating_duration_since(tokio::time::Instant::now()),
+            )
+            .await?;
+
+        match run.status {
+            RunStatus::Completed => {
+                serde_json::from_value(run.output.clone()).map_err(|err| ClientError::Decode {
+                    body: run.output.to_string(),
+                    source: err,
+                })
+            }
+            RunStatus::Failed => Err(ClientError::RunFailed {
+                error: serde_json::from_value::<StepError>(run.output.clone())
+                    .unwrap_or_else(|_| StepError::new("Error", run.output.to_string())),
+                run_id: run.run_id,
+            }),
+            status => Err(ClientError::RunFailed {
+                error: StepError::new("Error", format!("run finished with status {}", status)),
+                run_id: run.run_id,
+            }),
+        }
+    }
+
     pub(crate) async fn send_owned_events_with_ids<T: InngestEvent>(
         &self,
         evts: &[Event<T>],
-    ) -> Result<Vec<String>, DevError> {
+    ) -> Result<Vec<String>, ClientError> {
+        Self::validate_events(evts).map_err(ClientError::InvalidRequest)?;
         self.send_payload(evts).await.map(|response| response.ids)
     }
 
+    fn validate_events<'a, T: InngestEvent>(
+        evts: impl IntoIterator<Item = &'a Event<T>>,
+    ) -> Result<(), String> {
+        for evt in evts {
+            evt.validate()?;
+        }
+
+        Ok(())
+    }
+
     async fn send_payload<T: serde::Serialize + ?Sized>(
         &self,
         payload: &T,
-    ) -> Result<SendEventResponse, DevError> {
+    ) -> Result<SendEventResponse, ClientError> {
         let event_url = self.event_api_url();
-        let response = self
-            .http
-            .post(event_url)
-            .json(payload)
-            .send()
-            .await
-            .map_err(|err| DevError::Basic(format!("{}", err)))?;
+        let response = self.http.post(event_url).json(payload).send().await?;
 
         let http_status = response.status();
-        let body: SendEventResponse = response.json().await.map_err(|err| {
-            DevError::Basic(format!("error decoding event send response: {}", err))
-        })?;
+        let raw = response.text().await?;
+        let body: SendEventResponse = match serde_json::from_str(&raw) {
+            Ok(body) => body,
+            Err(_) if !http_status.is_success() => {
+                return Err(ClientError::Api {
+                    status: http_status.as_u16(),
+                    body: raw,
+                    message: None,
+                });
+            }
+            Err(err) => {
+                return Err(ClientError::Decode {
+                    body: raw,
+                    source: err,
+                });
+            }
+        };
 
         if !http_status.is_success() || body.status != 200 || body.error.is_some() {
-            let message = body.error.unwrap_or_else(|| {
-                format!(
-                    "unexpected event send response (http {}, body {})",
-                    http_status.as_u16(),
-                    body.status
-                )
+            let status = if http_status.is_success() {
+                body.status
+            } else {
+                http_status.as_u16()
+            };
+
+            return Err(ClientError::Api {
+                status,
+                body: raw,
+                message: body.error,
             });
-            return Err(DevError::Basic(message));
         }
 
         Ok(body)
     }
 
+    pub(crate) fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+
+    /// Sends a REST API request authenticated with the given signing keys.
+    ///
+    /// The request is retried once with the fallback key when the primary key
+    /// is rejected with a `401`.
+    pub(crate) async fn send_api_request(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&Value>,
+        signing_key: Option<&str>,
+        signing_key_fallback: Option<&str>,
+    ) -> Result<reqwest::Response, ClientError> {
+        let mut response = self
+            .send_api_request_with_key(method.clone(), path, body, signing_key)
+            .await?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED
+            && signing_key.is_some()
+            && signing_key_fallback.is_some()
+        {
+            response = self
+                .send_api_request_with_key(method, path, body, signing_key_fallback)
+                .await?;
+        }
+
+        Ok(response)
+    }
+
+    async fn send_api_request_with_key(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&Value>,
+        auth_key: Option<&str>,
+    ) -> Result<reqwest::Response, ClientError> {
+        let url = format!(
+            "{}{}",
+            self.inngest_api_origin().trim_end_matches('/'),
+            path
+        );
+        let mut request = self
+            .http
+            .request(method, url)
+            .header(header::INNGEST_SDK, version::sdk())
+            .header(header::INNGEST_REQ_VERSION, EXECUTION_VERSION);
+
+        if let Some(env) = self.env.clone() {
+            request = request.header(header::INNGEST_ENV, env);
+        }
+
+        if let Some(key) = auth_key {
+            let hashed = Signature::new(key).hash().map_err(|_| {
+                ClientError::InvalidRequest("error hashing signing key".to_string())
+            })?;
+            request = request.header("authorization", format!("Bearer {}", hashed));
+        }
+
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        Ok(request.send().await?)
+    }
+
     fn event_api_url(&self) -> String {
         let origin = self.inngest_evt_api_origin();
         let event_key = self.inngest_evt_api_key();
@@ -263,10 +542,22 @@ mod dev_mode_tests {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use axum::{routing::post, Json, Router};
+    use crate::utils::test_server::spawn_test_server;
+    use axum::{
+        extract::State,
+        http::StatusCode,
+        routing::{get, post},
+        Json, Router,
+    };
     use serde::{Deserialize, Serialize};
     use serde_json::{json, Value};
-    use std::net::TcpListener;
+    use std::{
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+    };
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
     struct TestEventData {
@@ -282,20 +573,11 @@ mod tests {
             }))
         }
 
-        let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
-        let addr = listener.local_addr().expect("listener addr should exist");
         let app = Router::new().route("/e/:event_key", post(ingest));
-
-        tokio::spawn(async move {
-            axum::Server::from_tcp(listener)
-                .expect("server should bind")
-                .serve(app.into_make_service())
-                .await
-                .expect("server should serve");
-        });
+        let origin = spawn_test_server(app);
 
         let client = Inngest::new("test-app")
-            .event_api_origin(&format!("http://{}", addr))
+            .event_api_origin(&origin)
             .event_key("test-key");
         let response = client
             .send_event(&Event::new(
@@ -317,6 +599,252 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn send_event_uses_the_injected_http_client() {
+        async fn ingest(headers: axum::http::HeaderMap) -> Json<Value> {
+            let transport = headers
+                .get("x-test-transport")
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+
+            Json(json!({ "ids": [transport], "status": 200 }))
+        }
+
+        let app = Router::new().route("/e/:event_key", post(ingest));
+        let origin = spawn_test_server(app);
+
+        let mut default_headers = reqwest::header::HeaderMap::new();
+        default_headers.insert("x-test-transport", "custom".parse().unwrap());
+        let http = reqwest::Client::builder()
+            .default_headers(default_headers)
+            .build()
+            .expect("custom client should build");
+
+        let client = Inngest::new("test-app")
+            .event_api_origin(&origin)
+            .event_key("test-key")
+            .http_client(http);
+        let response = client
+            .send_event(&Event::new(
+                "test/send",
+                TestEventData {
+                    value: "hello".to_string(),
+                },
+            ))
+            .await
+            .expect("send_event should succeed");
+
+        assert_eq!(response.ids, vec!["custom".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn send_event_returns_typed_api_errors() {
+        async fn ingest() -> (StatusCode, Json<Value>) {
+            (
+                StatusCode::UNAUTHORIZED,
+                Json(json!({ "error": "Event key not found", "status": 401 })),
+            )
+        }
+
+        let app = Router::new().route("/e/:event_key", post(ingest));
+        let origin = spawn_test_server(app);
+
+        let client = Inngest::new("test-app")
+            .event_api_origin(&origin)
+            .event_key("bad-key");
+        let err = client
+            .send_event(&Event::new(
+                "test/send",
+                TestEventData {
+                    value: "hello".to_string(),
+                },
+            ))
+            .await
+            .expect_err("a rejected event key should fail");
+
+        assert_eq!(err.status(), Some(401));
+        assert!(!err.is_retryable());
+        assert!(err.body().unwrap().contains("Event key not found"));
+        match err {
+            ClientError::Api { message, .. } => {
+                assert_eq!(message, Some("Event key not found".to_string()));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn send_events_rejects_reserved_event_names_before_sending() {
+        let client = Inngest::new("test-app")
+            .event_api_origin("http://127.0.0.1:1")
+            .event_key("test-key");
+        let valid = Event::new(
+            "test/send",
+            TestEventData {
+                value: "hello".to_string(),
+            },
+        );
+        let reserved = Event::new(
+            "inngest/function.finished",
+            TestEventData {
+                value: "hello".to_string(),
+            },
+        );
+
+        let err = client
+            .send_events(&[&valid, &reserved])
+            .await
+            .expect_err("reserved names should be rejected");
+
+        match err {
+            ClientError::InvalidRequest(message) => {
+                assert!(message.contains("reserved inngest/ prefix"), "{message}");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn send_event_reports_network_failures_as_retryable() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
+        let addr = listener.local_addr().expect("listener addr should exist");
+        drop(listener);
+
+        let client = Inngest::new("test-app")
+            .event_api_origin(&format!("http://{}", addr))
+            .event_key("test-key");
+        let err = client
+            .send_event(&Event::new(
+                "test/send",
+                TestEventData {
+                    value: "hello".to_string(),
+                },
+            ))
+            .await
+            .expect_err("an unreachable event API should fail");
+
+        assert!(matches!(err, ClientError::Network(_)));
+        assert!(err.is_retryable());
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[tokio::test]
+    async fn invoke_sends_function_invoked_event_and_returns_run_output() {
+        #[derive(Clone, Default)]
+        struct InvokeApiState {
+            event_body: Arc<Mutex<Option<Value>>>,
+            polls: Arc<AtomicUsize>,
+        }
+
+        async fn ingest(
+            State(state): State<InvokeApiState>,
+            Json(body): Json<Value>,
+        ) -> Json<Value> {
+            *state.event_body.lock().unwrap() = Some(body);
+            Json(json!({ "ids": ["evt-1"], "status": 200 }))
+        }
+
+        async fn event_runs(State(state): State<InvokeApiState>) -> Json<Value> {
+            // The first lookup happens before the executor has scheduled the run.
+            if state.polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                return Json(json!({ "data": [] }));
+            }
+
+            Json(json!({ "data": [{ "run_id": "run-1", "status": "Running" }] }))
+        }
+
+        async fn run(State(state): State<InvokeApiState>) -> Json<Value> {
+            if state.polls.fetch_add(1, Ordering::SeqCst) < 3 {
+                return Json(json!({ "data": { "run_id": "run-1", "status": "Running" } }));
+            }
+
+            Json(json!({
+                "data": {
+                    "run_id": "run-1",
+                    "status": "Completed",
+                    "output": { "value": "done" }
+                }
+            }))
+        }
+
+        let state = InvokeApiState::default();
+        let app = Router::new()
+            .route("/e/:event_key", post(ingest))
+            .route("/v1/events/:event_id/runs", get(event_runs))
+            .route("/v1/runs/:run_id", get(run))
+            .with_state(state.clone());
+        let origin = spawn_test_server(app);
+
+        let client = Inngest::new("test-app").dev(&origin);
+        let output: TestEventData = client
+            .invoke(
+                "test-app-target",
+                TestEventData {
+                    value: "hello".to_string(),
+                },
+                Duration::from_secs(5),
+            )
+            .await
+            .expect("invoke should resolve with the run output");
+
+        assert_eq!(output.value, "done");
+        assert_eq!(
+            state.event_body.lock().unwrap().clone(),
+            Some(json!([{
+                "id": null,
+                "name": "inngest/function.invoked",
+                "data": {
+                    "value": "hello",
+                    "_inngest": { "fn_id": "test-app-target" }
+                },
+                "ts": null,
+                "v": null
+            }]))
+        );
+    }
+
+    #[tokio::test]
+    async fn invoke_surfaces_failed_runs_as_step_errors() {
+        async fn ingest() -> Json<Value> {
+            Json(json!({ "ids": ["evt-1"], "status": 200 }))
+        }
+
+        async fn event_runs() -> Json<Value> {
+            Json(json!({ "data": [{ "run_id": "run-1", "status": "Failed" }] }))
+        }
+
+        async fn run() -> Json<Value> {
+            Json(json!({
+                "data": {
+                    "run_id": "run-1",
+                    "status": "Failed",
+                    "output": { "name": "Error", "message": "boom" }
+                }
+            }))
+        }
+
+        let app = Router::new()
+            .route("/e/:event_key", post(ingest))
+            .route("/v1/events/:event_id/runs", get(event_runs))
+            .route("/v1/runs/:run_id", get(run));
+        let origin = spawn_test_server(app);
+
+        let client = Inngest::new("test-app").dev(&origin);
+        let result = client
+            .invoke::<Value>("test-app-target", json!({}), Duration::from_secs(5))
+            .await;
+
+        match result {
+            Err(ClientError::RunFailed { run_id, error }) => {
+                assert_eq!(run_id, "run-1");
+                assert_eq!(error.name, "Error");
+                assert_eq!(error.message, "boom");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
     #[test]
     fn event_api_url_normalizes_trailing_slash() {
         let with_slash = Inngest::new("test-app")
@@ -1,68 +1,497 @@
-use std::env;
+//! Layered SDK configuration.
+//!
+//! Values are resolved from, in increasing order of precedence, an
+//! `inngest.toml` file, `INNGEST_*` environment variables and overrides set
+//! in code. Every resolved value remembers where it came from so
+//! misconfigured deployments can be debugged with [`InngestConfig::sources`].
 
-// client side
-const INNGEST_API_ORIGIN: &str = "INNGEST_API_ORIGIN";
-const INNGEST_EVENT_API_ORIGIN: &str = "INNGEST_EVENT_API_ORIGIN";
-const INNGEST_EVENT_KEY: &str = "INNGEST_EVENT_KEY";
-const INNGEST_ENV: &str = "INNGEST_ENV";
-const INNGEST_DEV: &str = "INNGEST_DEV";
+use std::{
+    collections::HashMap,
+    env,
+    fmt::{self, Display},
+    fs,
+    path::{Path, PathBuf},
+};
 
-// server side
-const INNGEST_SIGNING_KEY: &str = "INNGEST_SIGNING_KEY";
-const INNGEST_SIGNING_KEY_FALLBACK: &str = "INNGEST_SIGNING_KEY_FALLBACK";
-const INNGEST_SERVE_ORIGIN: &str = "INNGEST_SERVE_ORIGIN";
-const INNGEST_SERVE_PATH: &str = "INNGEST_SERVE_PATH";
+use serde::Deserialize;
 
-pub(crate) struct Config {}
+use crate::result::ConfigError;
 
-impl Config {
-    pub fn api_origin() -> Option<String> {
-        Self::read_env_str(INNGEST_API_ORIGIN)
+/// The file name looked up by [`InngestConfig::load`].
+pub const CONFIG_FILE_NAME: &str = "inngest.toml";
+
+/// A single configuration setting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConfigKey {
+    ApiOrigin,
+    EventApiOrigin,
+    /// Base URL used for both the REST and event APIs unless they are set
+    /// individually.
+    BaseUrl,
+    EventKey,
+    Env,
+    Dev,
+    SigningKey,
+    SigningKeyFallback,
+    ServeOrigin,
+    ServePath,
+}
+
+impl ConfigKey {
+    pub const ALL: [ConfigKey; 10] = [
+        ConfigKey::ApiOrigin,
+        ConfigKey::EventApiOrigin,
+        ConfigKey::BaseUrl,
+        ConfigKey::EventKey,
+        ConfigKey::Env,
+        ConfigKey::Dev,
+        ConfigKey::SigningKey,
+        ConfigKey::SigningKeyFallback,
+        ConfigKey::ServeOrigin,
+        ConfigKey::ServePath,
+    ];
+
+    /// The key used in `inngest.toml`.
+    pub fn file_key(&self) -> &'static str {
+        match self {
+            Self::ApiOrigin => "api_origin",
+            Self::EventApiOrigin => "event_api_origin",
+            Self::BaseUrl => "base_url",
+            Self::EventKey => "event_key",
+            Self::Env => "env",
+            Self::Dev => "dev",
+            Self::SigningKey => "signing_key",
+            Self::SigningKeyFallback => "signing_key_fallback",
+            Self::ServeOrigin => "serve_origin",
+            Self::ServePath => "serve_path",
+        }
     }
 
-    pub fn event_api_origin() -> Option<String> {
-        Self::read_env_str(INNGEST_EVENT_API_ORIGIN)
+    /// The environment variable the key is read from.
+    pub fn env_var(&self) -> &'static str {
+        match self {
+            Self::ApiOrigin => "INNGEST_API_ORIGIN",
+            Self::EventApiOrigin => "INNGEST_EVENT_API_ORIGIN",
+            Self::BaseUrl => "INNGEST_BASE_URL",
+            Self::EventKey => "INNGEST_EVENT_KEY",
+            Self::Env => "INNGEST_ENV",
+            Self::Dev => "INNGEST_DEV",
+            Self::SigningKey => "INNGEST_SIGNING_KEY",
+            Self::SigningKeyFallback => "INNGEST_SIGNING_KEY_FALLBACK",
+            Self::ServeOrigin => "INNGEST_SERVE_ORIGIN",
+            Self::ServePath => "INNGEST_SERVE_PATH",
+        }
     }
 
-    pub fn event_key() -> Option<String> {
-        Self::read_env_str(INNGEST_EVENT_KEY)
+    fn is_secret(&self) -> bool {
+        matches!(
+            self,
+            Self::EventKey | Self::SigningKey | Self::SigningKeyFallback
+        )
     }
+}
 
-    pub fn env() -> Option<String> {
-        Self::read_env_str(INNGEST_ENV)
+impl Display for ConfigKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file_key())
     }
+}
+
+/// Where a resolved configuration value came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Read from a configuration file.
+    File(PathBuf),
+    /// Read from an environment variable.
+    Env(&'static str),
+    /// Set in code through a builder method.
+    Override,
+}
 
-    pub fn dev() -> Option<String> {
-        Self::read_env_str(INNGEST_DEV)
+impl Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Env(var) => write!(f, "environment variable {}", var),
+            Self::Override => write!(f, "code override"),
+        }
     }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Layer {
+    values: HashMap<ConfigKey, (String, ConfigSource)>,
+}
 
-    pub fn signing_key() -> Option<String> {
-        Self::read_env_str(INNGEST_SIGNING_KEY)
+impl Layer {
+    fn get(&self, key: ConfigKey) -> Option<(&str, &ConfigSource)> {
+        self.values
+            .get(&key)
+            .map(|(value, source)| (value.as_str(), source))
     }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FileValue {
+    String(String),
+    Bool(bool),
+}
 
-    pub fn signing_key_fallback() -> Option<String> {
-        Self::read_env_str(INNGEST_SIGNING_KEY_FALLBACK)
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    api_origin: Option<String>,
+    event_api_origin: Option<String>,
+    base_url: Option<String>,
+    event_key: Option<String>,
+    env: Option<String>,
+    dev: Option<FileValue>,
+    signing_key: Option<String>,
+    signing_key_fallback: Option<String>,
+    serve_origin: Option<String>,
+    serve_path: Option<String>,
+}
+
+/// SDK configuration resolved from a config file, the environment and code.
+///
+/// Precedence is code overrides, then environment variables, then the file,
+/// regardless of the order the layers are added in. `INNGEST_BASE_URL` /
+/// `base_url` applies to both API origins unless a layer with equal or higher
+/// precedence sets them individually.
+#[derive(Clone, Debug, Default)]
+pub struct InngestConfig {
+    file: Layer,
+    env: Layer,
+    overrides: Layer,
+    issues: Vec<ConfigError>,
+}
+
+impl InngestConfig {
+    /// Creates an empty configuration.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn serve_origin() -> Option<String> {
-        Self::read_env_str(INNGEST_SERVE_ORIGIN)
+    /// Loads `inngest.toml` from the working directory, if present, and the
+    /// environment.
+    ///
+    /// Unlike [`InngestConfig::from_env`], invalid environment values are
+    /// returned as errors.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = Self::new();
+        if Path::new(CONFIG_FILE_NAME).is_file() {
+            config = config.with_file(CONFIG_FILE_NAME)?;
+        }
+
+        let config = config.with_env();
+        match config.issues.first() {
+            Some(issue) => Err(issue.clone()),
+            None => Ok(config),
+        }
     }
 
-    pub fn serve_path() -> Option<String> {
-        Self::read_env_str(INNGEST_SERVE_PATH)
+    /// Reads configuration from the environment only.
+    ///
+    /// Variables that are not valid unicode are skipped and reported through
+    /// [`InngestConfig::issues`].
+    pub fn from_env() -> Self {
+        Self::new().with_env()
     }
 
-    // helper methods
-    fn read_env_str(key: &str) -> Option<String> {
-        match env::var(key) {
-            Ok(str) => Some(str),
-            Err(err) => {
-                if let env::VarError::NotUnicode(v) = err {
-                    println!("Invalid environment variable value '{}': {:?}", key, v);
+    /// Adds the values from a TOML configuration file.
+    pub fn with_file(mut self, path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref().to_path_buf();
+        let contents = fs::read_to_string(&path).map_err(|err| ConfigError::Io {
+            path: path.clone(),
+            message: err.to_string(),
+        })?;
+
+        self.file = Self::parse_file(&contents, &path)?;
+        Ok(self)
+    }
+
+    /// Adds the values from `INNGEST_*` environment variables.
+    pub fn with_env(self) -> Self {
+        self.with_env_from(|var| env::var(var))
+    }
+
+    fn with_env_from(mut self, lookup: impl Fn(&str) -> Result<String, env::VarError>) -> Self {
+        let mut layer = Layer::default();
+
+        for key in ConfigKey::ALL {
+            let var = key.env_var();
+            match lookup(var) {
+                Ok(value) => {
+                    layer.values.insert(key, (value, ConfigSource::Env(var)));
                 }
+                Err(env::VarError::NotUnicode(_)) => {
+                    self.issues.push(ConfigError::InvalidEnv { var });
+                }
+                Err(env::VarError::NotPresent) => {}
+            }
+        }
 
-                None
+        self.env = layer;
+        self
+    }
+
+    fn parse_file(contents: &str, path: &Path) -> Result<Layer, ConfigError> {
+        let file: FileConfig = toml::from_str(contents).map_err(|err| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: err.message().to_string(),
+        })?;
+
+        let dev = file.dev.map(|dev| match dev {
+            FileValue::String(value) => value,
+            FileValue::Bool(true) => "1".to_string(),
+            FileValue::Bool(false) => "0".to_string(),
+        });
+
+        let mut layer = Layer::default();
+        for (key, value) in [
+            (ConfigKey::ApiOrigin, file.api_origin),
+            (ConfigKey::EventApiOrigin, file.event_api_origin),
+            (ConfigKey::BaseUrl, file.base_url),
+            (ConfigKey::EventKey, file.event_key),
+            (ConfigKey::Env, file.env),
+            (ConfigKey::Dev, dev),
+            (ConfigKey::SigningKey, file.signing_key),
+            (ConfigKey::SigningKeyFallback, file.signing_key_fallback),
+            (ConfigKey::ServeOrigin, file.serve_origin),
+            (ConfigKey::ServePath, file.serve_path),
+        ] {
+            if let Some(value) = value {
+                layer
+                    .values
+                    .insert(key, (value, ConfigSource::File(path.to_path_buf())));
             }
         }
+
+        Ok(layer)
+    }
+
+    /// Overrides a single value in code.
+    pub fn set(mut self, key: ConfigKey, value: &str) -> Self {
+        self.overrides
+            .values
+            .insert(key, (value.to_string(), ConfigSource::Override));
+        self
+    }
+
+    pub fn api_origin(self, url: &str) -> Self {
+        self.set(ConfigKey::ApiOrigin, url)
+    }
+
+    pub fn event_api_origin(self, url: &str) -> Self {
+        self.set(ConfigKey::EventApiOrigin, url)
+    }
+
+    pub fn base_url(self, url: &str) -> Self {
+        self.set(ConfigKey::BaseUrl, url)
+    }
+
+    pub fn event_key(self, key: &str) -> Self {
+        self.set(ConfigKey::EventKey, key)
+    }
+
+    pub fn env(self, env: &str) -> Self {
+        self.set(ConfigKey::Env, env)
+    }
+
+    pub fn dev(self, dev: &str) -> Self {
+        self.set(ConfigKey::Dev, dev)
+    }
+
+    pub fn signing_key(self, key: &str) -> Self {
+        self.set(ConfigKey::SigningKey, key)
+    }
+
+    pub fn signing_key_fallback(self, key: &str) -> Self {
+        self.set(ConfigKey::SigningKeyFallback, key)
+    }
+
+    pub fn serve_origin(self, origin: &str) -> Self {
+        self.set(ConfigKey::ServeOrigin, origin)
+    }
+
+    pub fn serve_path(self, path: &str) -> Self {
+        self.set(ConfigKey::ServePath, path)
+    }
+
+    /// Returns the resolved value of `key` together with its source.
+    ///
+    /// The API origins fall back to the base URL of the same layer before
+    /// lower-precedence layers are consulted.
+    pub fn resolve(&self, key: ConfigKey) -> Option<(&str, &ConfigSource)> {
+        [&self.overrides, &self.env, &self.file]
+            .into_iter()
+            .find_map(|layer| {
+                layer.get(key).or_else(|| match key {
+                    ConfigKey::ApiOrigin | ConfigKey::EventApiOrigin => {
+                        layer.get(ConfigKey::BaseUrl)
+                    }
+                    _ => None,
+                })
+            })
+    }
+
+    /// Returns the resolved value of `key`.
+    pub fn get(&self, key: ConfigKey) -> Option<&str> {
+        self.resolve(key).map(|(value, _)| value)
+    }
+
+    /// Returns where the resolved value of `key` came from.
+    pub fn source(&self, key: ConfigKey) -> Option<&ConfigSource> {
+        self.resolve(key).map(|(_, source)| source)
+    }
+
+    /// Lists the source of every resolved value.
+    pub fn sources(&self) -> Vec<(ConfigKey, &ConfigSource)> {
+        ConfigKey::ALL
+            .into_iter()
+            .filter_map(|key| self.source(key).map(|source| (key, source)))
+            .collect()
+    }
+
+    /// Problems found while reading the environment that did not prevent the
+    /// configuration from loading.
+    pub fn issues(&self) -> &[ConfigError] {
+        &self.issues
+    }
+}
+
+/// Lists resolved values and their sources, with keys redacted.
+impl Display for InngestConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for key in ConfigKey::ALL {
+            if let Some((value, source)) = self.resolve(key) {
+                let value = if key.is_secret() { "<redacted>" } else { value };
+                writeln!(f, "{} = {} ({})", key, value, source)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_from(
+        vars: &'static [(&'static str, &'static str)],
+    ) -> impl Fn(&str) -> Result<String, env::VarError> {
+        move |var| {
+            vars.iter()
+                .find(|(key, _)| *key == var)
+                .map(|(_, value)| value.to_string())
+                .ok_or(env::VarError::NotPresent)
+        }
+    }
+
+    fn file_config(contents: &str) -> InngestConfig {
+        InngestConfig {
+            file: InngestConfig::parse_file(contents, Path::new("inngest.toml")).unwrap(),
+            ..InngestConfig::default()
+        }
+    }
+
+    #[test]
+    fn overrides_beat_env_which_beats_file() {
+        let config = file_config(
+            r#"
+            event_key = "from-file"
+            env = "file-env"
+            serve_path = "/file"
+            "#,
+        )
+        .signing_key("from-code")
+        .event_key("from-code")
+        .with_env_from(env_from(&[
+            ("INNGEST_EVENT_KEY", "from-env"),
+            ("INNGEST_ENV", "env-env"),
+            ("INNGEST_SIGNING_KEY", "from-env"),
+        ]));
+
+        assert_eq!(config.get(ConfigKey::EventKey), Some("from-code"));
+        assert_eq!(config.get(ConfigKey::SigningKey), Some("from-code"));
+        assert_eq!(config.get(ConfigKey::Env), Some("env-env"));
+        assert_eq!(config.get(ConfigKey::ServePath), Some("/file"));
+        assert_eq!(config.get(ConfigKey::ServeOrigin), None);
+
+        assert_eq!(
+            config.source(ConfigKey::Env),
+            Some(&ConfigSource::Env("INNGEST_ENV"))
+        );
+        assert_eq!(
+            config.source(ConfigKey::ServePath),
+            Some(&ConfigSource::File(PathBuf::from("inngest.toml")))
+        );
+        assert_eq!(
+            config.source(ConfigKey::EventKey),
+            Some(&ConfigSource::Override)
+        );
+    }
+
+    #[test]
+    fn base_url_applies_to_both_api_origins() {
+        let config =
+            file_config(r#"api_origin = "https://file.example.com""#).with_env_from(env_from(&[
+                ("INNGEST_BASE_URL", "http://localhost:9999"),
+                ("INNGEST_EVENT_API_ORIGIN", "http://events:8288"),
+            ]));
+
+        assert_eq!(
+            config.resolve(ConfigKey::ApiOrigin),
+            Some((
+                "http://localhost:9999",
+                &ConfigSource::Env("INNGEST_BASE_URL")
+            ))
+        );
+        assert_eq!(
+            config.get(ConfigKey::EventApiOrigin),
+            Some("http://events:8288")
+        );
+    }
+
+    #[test]
+    fn file_accepts_boolean_dev_and_rejects_unknown_keys() {
+        assert_eq!(file_config("dev = true").get(ConfigKey::Dev), Some("1"));
+        assert_eq!(file_config("dev = false").get(ConfigKey::Dev), Some("0"));
+
+        let err = InngestConfig::parse_file("event_keys = \"typo\"", Path::new("inngest.toml"))
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { .. }));
+        assert!(err.to_string().contains("inngest.toml"));
+    }
+
+    #[test]
+    fn invalid_env_values_are_reported_as_issues() {
+        let config = InngestConfig::new().with_env_from(|var| {
+            if var == "INNGEST_ENV" {
+                Err(env::VarError::NotUnicode("\u{fffd}".into()))
+            } else {
+                Err(env::VarError::NotPresent)
+            }
+        });
+
+        assert_eq!(config.get(ConfigKey::Env), None);
+        assert_eq!(
+            config.issues(),
+            &[ConfigError::InvalidEnv { var: "INNGEST_ENV" }]
+        );
+    }
+
+    #[test]
+    fn display_redacts_secrets() {
+        let output = InngestConfig::new()
+            .signing_key("signkey-prod-secret")
+            .serve_path("/api/inngest")
+            .to_string();
+
+        assert!(output.contains("signing_key = <redacted> (code override)"));
+        assert!(output.contains("serve_path = /api/inngest (code override)"));
+        assert!(!output.contains("signkey-prod-secret"));
     }
 }
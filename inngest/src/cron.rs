@@ -0,0 +1,393 @@
+//! Typed cron schedules for function triggers.
+//!
+//! Expressions use the standard five fields (`minute hour day-of-month month
+//! day-of-week`), the `@hourly`/`@daily`/`@weekly`/`@monthly`/`@yearly`
+//! macros and an optional `TZ=<zone>` prefix, e.g. `TZ=Europe/Paris 0 12 * * 5`.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{function::FunctionTime, utils::time};
+
+const TZ_PREFIX: &str = "TZ=";
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+// Five years covers every valid schedule, including Feb 29 on leap years.
+const MAX_SEARCH_DAYS: i64 = 366 * 5;
+
+/// A cron schedule with an optional timezone and start jitter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    pub schedule: String,
+    pub timezone: Option<String>,
+    pub jitter: Option<FunctionTime>,
+}
+
+impl Cron {
+    /// Creates a cron trigger from a five-field schedule or macro.
+    pub fn new(schedule: &str) -> Self {
+        Self {
+            schedule: schedule.trim().to_string(),
+            timezone: None,
+            jitter: None,
+        }
+    }
+
+    /// Parses a full cron expression, including an optional `TZ=` prefix.
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let expr = expr.trim();
+        let cron = match expr.strip_prefix(TZ_PREFIX) {
+            Some(rest) => match rest.split_once(char::is_whitespace) {
+                Some((tz, schedule)) => Self::new(schedule).timezone(tz),
+                None => return Err(format!("cron expression {} has no schedule", expr)),
+            },
+            None => Self::new(expr),
+        };
+
+        cron.validate()?;
+        Ok(cron)
+    }
+
+    /// Evaluates the schedule in the given IANA timezone, e.g.
+    /// `America/New_York`.
+    pub fn timezone(mut self, tz: &str) -> Self {
+        self.timezone = Some(tz.trim().to_string());
+        self
+    }
+
+    /// Delays each scheduled run by a random amount up to `jitter` to spread
+    /// load.
+    pub fn jitter<T: Into<FunctionTime>>(mut self, jitter: T) -> Self {
+        self.jitter = Some(jitter.into());
+        self
+    }
+
+    /// Returns the expression synced to Inngest, with the `TZ=` prefix when a
+    /// timezone is set.
+    pub fn expression(&self) -> String {
+        match &self.timezone {
+            Some(tz) => format!("{}{} {}", TZ_PREFIX, tz, self.schedule),
+            None => self.schedule.clone(),
+        }
+    }
+
+    /// Checks the schedule, timezone and jitter.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(tz) = &self.timezone {
+            let valid = !tz.is_empty()
+                && tz
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '+'));
+            if !valid {
+                return Err(format!("cron timezone {:?} is not a valid IANA zone", tz));
+            }
+        }
+
+        if let Some(jitter) = &self.jitter {
+            jitter.validate("cron jitter")?;
+        }
+
+        Schedule::parse(&self.schedule).map(|_| ())
+    }
+
+    /// Returns the first time after `after` that the schedule fires.
+    ///
+    /// Intended for tests: only schedules without a timezone, or in `UTC`, can
+    /// be evaluated since the SDK does not ship a timezone database. Jitter is
+    /// not applied.
+    pub fn next_after(&self, after: SystemTime) -> Result<SystemTime, String> {
+        Ok(self.next_fire_times(after, 1)?.remove(0))
+    }
+
+    /// Returns the next `count` times after `after` that the schedule fires.
+    pub fn next_fire_times(
+        &self,
+        after: SystemTime,
+        count: usize,
+    ) -> Result<Vec<SystemTime>, String> {
+        if let Some(tz) = &self.timezone {
+            if !matches!(tz.as_str(), "UTC" | "Etc/UTC") {
+                return Err(format!(
+                    "next fire times can only be computed for UTC schedules, not {}",
+                    tz
+                ));
+            }
+        }
+
+        let schedule = Schedule::parse(&self.schedule)?;
+        let mut times = Vec::with_capacity(count);
+        let mut cursor = after;
+        for _ in 0..count {
+            let next = schedule
+                .next_after(cursor)
+                .ok_or_else(|| format!("cron schedule {} never fires", self.schedule))?;
+            times.push(next);
+            cursor = next;
+        }
+
+        Ok(times)
+    }
+}
+
+/// A parsed five-field schedule stored as bitsets of allowed values.
+struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl Schedule {
+    fn parse(schedule: &str) -> Result<Self, String> {
+        let expanded = match schedule {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "cron schedule {:?} must have 5 fields, got {}",
+                schedule,
+                fields.len()
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], "day-of-week", 0, 7, &DAY_NAMES)?;
+        // Both 0 and 7 mean Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], "minute", 0, 59, &[])?,
+            hours: parse_field(fields[1], "hour", 0, 23, &[])?,
+            days_of_month: parse_field(fields[2], "day-of-month", 1, 31, &[])?,
+            months: parse_field(fields[3], "month", 1, 12, &MONTH_NAMES)?,
+            days_of_week,
+            dom_restricted: !is_wildcard(fields[2]),
+            dow_restricted: !is_wildcard(fields[4]),
+        })
+    }
+
+    fn matches_day(&self, month: u32, day: u32, weekday: u32) -> bool {
+        if self.months & (1 << month) == 0 {
+            return false;
+        }
+
+        let dom = self.days_of_month & (1 << day) != 0;
+        let dow = self.days_of_week & (1 << weekday) != 0;
+        // Standard cron semantics: when both day fields are restricted, either
+        // may match.
+        if self.dom_restricted && self.dow_restricted {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let secs = match after.duration_since(UNIX_EPOCH) {
+            Ok(dur) => dur.as_secs() as i64,
+            Err(err) => -(err.duration().as_secs() as i64) - 1,
+        };
+        let start_minute = secs.div_euclid(60) + 1;
+        let start_day = start_minute.div_euclid(1440);
+
+        for days in start_day..start_day + MAX_SEARCH_DAYS {
+            let (_, month, day) = time::civil_from_days(days);
+            // 1970-01-01 was a Thursday.
+            let weekday = (days + 4).rem_euclid(7) as u32;
+            if !self.matches_day(month, day, weekday) {
+                continue;
+            }
+
+            let first = if days == start_day {
+                start_minute.rem_euclid(1440)
+            } else {
+                0
+            };
+            for minute_of_day in first..1440 {
+                let (hour, minute) = (minute_of_day / 60, minute_of_day % 60);
+                if self.hours & (1 << hour) != 0 && self.minutes & (1 << minute) != 0 {
+                    let secs = (days * 1440 + minute_of_day) * 60;
+                    return Some(if secs >= 0 {
+                        UNIX_EPOCH + Duration::from_secs(secs as u64)
+                    } else {
+                        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+fn is_wildcard(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+fn parse_field(
+    field: &str,
+    label: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<u64, String> {
+    let mut bits = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| format!("cron {} step {:?} is not valid", label, step))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = match range {
+            "*" | "?" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (
+                    parse_value(start, label, min, max, names)?,
+                    parse_value(end, label, min, max, names)?,
+                ),
+                None => {
+                    let start = parse_value(range, label, min, max, names)?;
+                    (start, if part.contains('/') { max } else { start })
+                }
+            },
+        };
+
+        if start > end {
+            return Err(format!("cron {} range {:?} is reversed", label, range));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Ok(bits)
+}
+
+fn parse_value(
+    value: &str,
+    label: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+) -> Result<u32, String> {
+    let upper = value.to_ascii_uppercase();
+    // Named months start at 1, named weekdays at 0.
+    let named = names
+        .iter()
+        .position(|name| *name == upper)
+        .map(|idx| idx as u32 + min.min(1) * (names.len() == 12) as u32);
+
+    let parsed = named.or_else(|| value.parse::<u32>().ok());
+    match parsed {
+        Some(parsed) if (min..=max).contains(&parsed) => Ok(parsed),
+        _ => Err(format!(
+            "cron {} value {:?} must be between {} and {}",
+            label, value, min, max
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    // 2024-01-01T00:00:00Z, a Monday.
+    const JAN_1_2024: u64 = 1_704_067_200;
+
+    #[test]
+    fn parse_extracts_timezone_prefix() {
+        let cron = Cron::parse("TZ=Europe/Paris 0 12 * * FRI").unwrap();
+
+        assert_eq!(cron.schedule, "0 12 * * FRI");
+        assert_eq!(cron.timezone.as_deref(), Some("Europe/Paris"));
+        assert_eq!(cron.expression(), "TZ=Europe/Paris 0 12 * * FRI");
+    }
+
+    #[test]
+    fn validate_rejects_malformed_schedules() {
+        assert_eq!(
+            Cron::new("0 12 * *").validate(),
+            Err("cron schedule \"0 12 * *\" must have 5 fields, got 4".to_string())
+        );
+        assert_eq!(
+            Cron::new("61 * * * *").validate(),
+            Err("cron minute value \"61\" must be between 0 and 59".to_string())
+        );
+        assert_eq!(
+            Cron::new("*/0 * * * *").validate(),
+            Err("cron minute step \"0\" is not valid".to_string())
+        );
+        assert!(Cron::new("0 0 * * *")
+            .timezone("Not A Zone")
+            .validate()
+            .is_err());
+        assert!(Cron::new("@daily").jitter("").validate().is_err());
+        assert!(Cron::new("0 9-17/2 1,15 JAN-JUN MON-FRI")
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn next_fire_times_follow_the_schedule() {
+        let times = Cron::new("*/15 * * * *")
+            .next_fire_times(at(JAN_1_2024 + 1), 3)
+            .unwrap();
+        assert_eq!(
+            times,
+            vec![
+                at(JAN_1_2024 + 15 * 60),
+                at(JAN_1_2024 + 30 * 60),
+                at(JAN_1_2024 + 45 * 60)
+            ]
+        );
+
+        // The next Friday at noon after Monday, Jan 1st is Jan 5th.
+        assert_eq!(
+            Cron::new("0 12 * * FRI").next_after(at(JAN_1_2024)),
+            Ok(at(JAN_1_2024 + 4 * 86_400 + 12 * 3600))
+        );
+
+        // Feb 29th only exists on leap years.
+        assert_eq!(
+            Cron::new("0 0 29 2 *").next_after(at(JAN_1_2024)),
+            Ok(at(JAN_1_2024 + 59 * 86_400))
+        );
+    }
+
+    #[test]
+    fn next_fire_times_require_utc_schedules() {
+        assert!(Cron::new("@hourly")
+            .timezone("UTC")
+            .next_after(at(JAN_1_2024))
+            .is_ok());
+        assert!(Cron::new("@hourly")
+            .timezone("America/New_York")
+            .next_after(at(JAN_1_2024))
+            .is_err());
+    }
+}
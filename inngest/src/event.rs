@@ -1,9 +1,97 @@
 use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use serde_json::{Map, Value};
+use std::{
+    fmt::Debug,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Event names with this prefix are reserved for Inngest system events.
+const RESERVED_EVENT_PREFIX: &str = "inngest/";
 
 pub trait InngestEvent: Serialize + for<'a> Deserialize<'a> + Debug + 'static {}
 impl<T: Serialize + for<'a> Deserialize<'a> + Debug + 'static> InngestEvent for T {}
 
+/// A payload enum with one variant per event name, for functions triggered
+/// by several events.
+///
+/// The enum must be adjacently tagged by event name, with each variant
+/// renamed to the event it handles, and `event_names` must list those same
+/// names:
+///
+/// ```ignore
+/// #[derive(Debug, Deserialize, Serialize)]
+/// #[serde(tag = "name", content = "data")]
+/// enum Billing {
+///     #[serde(rename = "billing/invoice.paid")]
+///     Paid(Paid),
+///     #[serde(rename = "billing/invoice.refunded")]
+///     Refunded(Refunded),
+/// }
+///
+/// impl EventEnum for Billing {
+///     fn event_names() -> &'static [&'static str] {
+///         &["billing/invoice.paid", "billing/invoice.refunded"]
+///     }
+/// }
+/// ```
+///
+/// Syncing the app checks that `event_names` and the function's triggers
+/// match, but not that they match the serde renames.
+///
+/// Opt a function into dispatching by event name with
+/// [`ServableFn::dispatch_by_event_name`](crate::function::ServableFn::dispatch_by_event_name).
+pub trait EventEnum: InngestEvent {
+    /// The event names mapped to a variant.
+    fn event_names() -> &'static [&'static str];
+}
+
+/// Information about the user that caused an event.
+///
+/// Values are attached to the event envelope as `user` and are never logged
+/// by Inngest; any additional attributes can be set through `extra`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+pub struct EventUser {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phone: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl EventUser {
+    /// Creates an empty user payload.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the ID of the user in your own system.
+    pub fn external_id(mut self, external_id: &str) -> Self {
+        self.external_id = Some(external_id.to_string());
+        self
+    }
+
+    /// Sets the user's email address.
+    pub fn email(mut self, email: &str) -> Self {
+        self.email = Some(email.to_string());
+        self
+    }
+
+    /// Sets the user's phone number.
+    pub fn phone(mut self, phone: &str) -> Self {
+        self.phone = Some(phone.to_string());
+        self
+    }
+
+    /// Adds an arbitrary attribute to the user payload.
+    pub fn attr(mut self, key: &str, value: Value) -> Self {
+        self.extra.insert(key.to_string(), value);
+        self
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Event<T>
 where
@@ -12,6 +100,9 @@ where
     pub id: Option<String>,
     pub name: String,
     pub data: T,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<EventUser>,
+    /// Milliseconds since the unix epoch. Future timestamps delay delivery.
     #[serde(rename = "ts")]
     pub timestamp: Option<i64>,
     #[serde(rename = "v")]
@@ -27,6 +118,7 @@ where
             id: None,
             name: name.to_string(),
             data,
+            user: None,
             timestamp: None,
             version: None,
         }
@@ -42,8 +134,163 @@ where
         self
     }
 
+    /// Sets the event timestamp from a [`SystemTime`].
+    ///
+    /// A time in the future schedules the event so functions it triggers do
+    /// not start before then.
+    pub fn timestamp_at(mut self, time: SystemTime) -> Self {
+        self.timestamp = Some(system_time_to_ms(time));
+        self
+    }
+
+    /// Schedules the event to be delivered after `delay` from now.
+    pub fn deliver_in(self, delay: Duration) -> Self {
+        self.timestamp_at(SystemTime::now() + delay)
+    }
+
+    /// Returns the event timestamp as a [`SystemTime`], if one is set.
+    pub fn timestamp_as_system_time(&self) -> Option<SystemTime> {
+        self.timestamp.map(|ts| {
+            if ts >= 0 {
+                UNIX_EPOCH + Duration::from_millis(ts as u64)
+            } else {
+                UNIX_EPOCH - Duration::from_millis(ts.unsigned_abs())
+            }
+        })
+    }
+
     pub fn version(mut self, v: &str) -> Self {
         self.version = Some(v.to_string());
         self
     }
+
+    /// Attaches information about the user that caused the event.
+    pub fn user(mut self, user: EventUser) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    /// Validates the event envelope before it is sent.
+    ///
+    /// Names must be non-empty and must not use the reserved `inngest/`
+    /// prefix, `data` must serialize to a JSON object, and timestamps must not
+    /// be negative.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("event name cannot be empty".to_string());
+        }
+
+        if self.name.starts_with(RESERVED_EVENT_PREFIX) {
+            return Err(format!(
+                "event name {} uses the reserved {} prefix",
+                self.name, RESERVED_EVENT_PREFIX
+            ));
+        }
+
+        match serde_json::to_value(&self.data) {
+            Ok(Value::Object(_)) | Ok(Value::Null) => {}
+            Ok(_) => {
+                return Err(format!(
+                    "event {} data must serialize to a JSON object",
+                    self.name
+                ));
+            }
+            Err(err) => {
+                return Err(format!(
+                    "event {} data cannot be serialized: {}",
+                    self.name, err
+                ));
+            }
+        }
+
+        if matches!(self.timestamp, Some(ts) if ts < 0) {
+            return Err(format!("event {} timestamp cannot be negative", self.name));
+        }
+
+        Ok(())
+    }
+}
+
+fn system_time_to_ms(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(dur) => dur.as_millis() as i64,
+        Err(err) => -(err.duration().as_millis() as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct TestEventData {
+        value: String,
+    }
+
+    fn test_event(name: &str) -> Event<TestEventData> {
+        Event::new(
+            name,
+            TestEventData {
+                value: "hello".to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn serializes_user_only_when_present() {
+        let without_user = serde_json::to_value(test_event("test/event")).unwrap();
+        assert!(without_user.get("user").is_none());
+
+        let with_user = serde_json::to_value(
+            test_event("test/event").user(
+                EventUser::new()
+                    .external_id("user-1")
+                    .email("a@example.com")
+                    .attr("plan", json!("pro")),
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            with_user["user"],
+            json!({ "external_id": "user-1", "email": "a@example.com", "plan": "pro" })
+        );
+    }
+
+    #[test]
+    fn timestamp_helpers_round_trip_system_time() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_674_082_830_001);
+        let event = test_event("test/event").timestamp_at(time);
+
+        assert_eq!(event.timestamp, Some(1_674_082_830_001));
+        assert_eq!(event.timestamp_as_system_time(), Some(time));
+
+        let scheduled = test_event("test/event").deliver_in(Duration::from_secs(60));
+        assert!(scheduled.timestamp_as_system_time().unwrap() > SystemTime::now());
+    }
+
+    #[test]
+    fn validate_rejects_reserved_and_empty_names() {
+        assert!(test_event("test/event").validate().is_ok());
+        assert_eq!(
+            test_event("inngest/function.failed").validate(),
+            Err("event name inngest/function.failed uses the reserved inngest/ prefix".to_string())
+        );
+        assert_eq!(
+            test_event("  ").validate(),
+            Err("event name cannot be empty".to_string())
+        );
+    }
+
+    #[test]
+    fn validate_rejects_non_object_data_and_negative_timestamps() {
+        assert_eq!(
+            Event::new("test/event", json!([1, 2])).validate(),
+            Err("event test/event data must serialize to a JSON object".to_string())
+        );
+        assert_eq!(
+            test_event("test/event").timestamp(-1).validate(),
+            Err("event test/event timestamp cannot be negative".to_string())
+        );
+    }
 }
@@ -0,0 +1,505 @@
+//! Local validation for the CEL expressions used in function config.
+//!
+//! Inngest evaluates trigger filters, cancellation and wait conditions and
+//! every flow-control `key` server-side, so mistakes would otherwise only
+//! surface after a sync. This checker parses the CEL grammar and verifies
+//! that expressions only reference the variables available to them; it does
+//! not type-check or evaluate anything.
+
+use std::fmt;
+
+/// The triggering event, available to every expression.
+pub(crate) const EVENT_ROOT: &str = "event";
+/// The event being matched by `cancelOn` and `waitForEvent` conditions.
+pub(crate) const ASYNC_ROOT: &str = "async";
+
+/// Variables available to trigger expressions and flow-control keys.
+pub(crate) const EVENT_ROOTS: &[&str] = &[EVENT_ROOT];
+/// Variables available when matching another incoming event.
+pub(crate) const MATCH_ROOTS: &[&str] = &[EVENT_ROOT, ASYNC_ROOT];
+
+const COMPREHENSION_MACROS: &[&str] = &["all", "exists", "exists_one", "map", "filter"];
+
+/// Checks the syntax of a CEL expression and that it only references the
+/// `event` and `async` variables.
+///
+/// ```
+/// use inngest::expression::validate_expression;
+///
+/// assert!(validate_expression("event.data.userId == async.data.userId").is_ok());
+/// assert!(validate_expression("event.data.userId = async.data.userId").is_err());
+/// ```
+pub fn validate_expression(expr: &str) -> Result<(), String> {
+    validate_with_roots(expr, MATCH_ROOTS)
+}
+
+/// Checks an expression that may only reference the given root variables.
+pub(crate) fn validate_with_roots(expr: &str, roots: &[&str]) -> Result<(), String> {
+    if expr.trim().is_empty() {
+        return Err("expression cannot be empty".to_string());
+    }
+
+    let tokens = tokenize(expr)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        roots,
+        scope: Vec::new(),
+    };
+
+    parser.expr()?;
+    match parser.peek() {
+        Token::Eof => Ok(()),
+        token => Err(parser.unexpected(token)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Literal,
+    Punct(&'static str),
+    Eof,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "`{}`", name),
+            Token::Literal => write!(f, "literal"),
+            Token::Punct(punct) => write!(f, "`{}`", punct),
+            Token::Eof => write!(f, "end of expression"),
+        }
+    }
+}
+
+const PUNCTS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "(", ")", "[", "]", "{", "}", ".", ",", ":", "?", "+", "-",
+    "*", "/", "%", "!", "<", ">",
+];
+
+fn tokenize(expr: &str) -> Result<Vec<(Token, usize)>, String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let position = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        // String and bytes literals, with optional raw/bytes prefixes.
+        let prefix_len = chars[i..]
+            .iter()
+            .take(2)
+            .take_while(|c| matches!(c, 'r' | 'R' | 'b' | 'B'))
+            .count();
+        if matches!(chars.get(i + prefix_len), Some('"') | Some('\'')) {
+            let raw = chars[i..i + prefix_len]
+                .iter()
+                .any(|c| matches!(c, 'r' | 'R'));
+            i = scan_string(&chars, i + prefix_len, raw)
+                .ok_or_else(|| format!("unterminated string starting at position {}", position))?;
+            tokens.push((Token::Literal, position));
+            continue;
+        }
+
+        if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let ident: String = chars[start..i].iter().collect();
+            let token = match ident.as_str() {
+                "true" | "false" | "null" => Token::Literal,
+                "in" => Token::Punct("in"),
+                _ => Token::Ident(ident),
+            };
+            tokens.push((token, position));
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            i = scan_number(&chars, i);
+            tokens.push((Token::Literal, position));
+            continue;
+        }
+
+        let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        if let Some(punct) = PUNCTS.iter().find(|punct| rest.starts_with(*punct)) {
+            i += punct.len();
+            tokens.push((Token::Punct(punct), position));
+            continue;
+        }
+
+        let hint = match c {
+            '=' => ", did you mean `==`?",
+            '&' => ", did you mean `&&`?",
+            '|' => ", did you mean `||`?",
+            _ => "",
+        };
+        return Err(format!(
+            "unexpected character `{}` at position {}{}",
+            c, position, hint
+        ));
+    }
+
+    tokens.push((Token::Eof, chars.len() + 1));
+    Ok(tokens)
+}
+
+// Returns the index after the closing quote, or `None` if unterminated.
+fn scan_string(chars: &[char], start: usize, raw: bool) -> Option<usize> {
+    let quote = chars[start];
+    let triple = chars.get(start + 1) == Some(&quote) && chars.get(start + 2) == Some(&quote);
+    let delimiter = if triple { 3 } else { 1 };
+    let mut i = start + delimiter;
+
+    while i < chars.len() {
+        if chars[i] == '\\' && !raw {
+            i += 2;
+            continue;
+        }
+
+        if chars[i] == quote && chars[i..].iter().take(delimiter).all(|c| *c == quote) {
+            if chars[i..].len() >= delimiter {
+                return Some(i + delimiter);
+            }
+        } else if chars[i] == '\n' && !triple {
+            return None;
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+fn scan_number(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    if chars[i] == '0' && matches!(chars.get(i + 1), Some('x') | Some('X')) {
+        i += 2;
+        while i < chars.len() && chars[i].is_ascii_hexdigit() {
+            i += 1;
+        }
+    } else {
+        while i < chars.len() && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+        if chars.get(i) == Some(&'.') && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) {
+            i += 1;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+        if matches!(chars.get(i), Some('e') | Some('E')) {
+            let mut j = i + 1;
+            if matches!(chars.get(j), Some('+') | Some('-')) {
+                j += 1;
+            }
+            if chars.get(j).is_some_and(|c| c.is_ascii_digit()) {
+                i = j;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+        }
+    }
+
+    if matches!(chars.get(i), Some('u') | Some('U')) {
+        i += 1;
+    }
+
+    i
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    roots: &'a [&'a str],
+    scope: Vec<String>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Token {
+        self.tokens[self.pos].0.clone()
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.peek();
+        if token != Token::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, punct: &str) -> bool {
+        if matches!(self.peek(), Token::Punct(p) if p == punct) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, punct: &str) -> Result<(), String> {
+        if self.eat(punct) {
+            return Ok(());
+        }
+
+        let token = self.peek();
+        Err(format!(
+            "expected `{}` but found {} at position {}",
+            punct,
+            token,
+            self.position()
+        ))
+    }
+
+    fn unexpected(&self, token: Token) -> String {
+        format!("unexpected {} at position {}", token, self.position())
+    }
+
+    // expr := or ('?' expr ':' expr)?
+    fn expr(&mut self) -> Result<(), String> {
+        self.or()?;
+        if self.eat("?") {
+            self.expr()?;
+            self.expect(":")?;
+            self.expr()?;
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<(), String> {
+        self.and()?;
+        while self.eat("||") {
+            self.and()?;
+        }
+        Ok(())
+    }
+
+    fn and(&mut self) -> Result<(), String> {
+        self.relation()?;
+        while self.eat("&&") {
+            self.relation()?;
+        }
+        Ok(())
+    }
+
+    fn relation(&mut self) -> Result<(), String> {
+        self.addition()?;
+        while ["==", "!=", "<", "<=", ">", ">=", "in"]
+            .iter()
+            .any(|op| self.eat(op))
+        {
+            self.addition()?;
+        }
+        Ok(())
+    }
+
+    fn addition(&mut self) -> Result<(), String> {
+        self.multiplication()?;
+        while self.eat("+") || self.eat("-") {
+            self.multiplication()?;
+        }
+        Ok(())
+    }
+
+    fn multiplication(&mut self) -> Result<(), String> {
+        self.unary()?;
+        while self.eat("*") || self.eat("/") || self.eat("%") {
+            self.unary()?;
+        }
+        Ok(())
+    }
+
+    fn unary(&mut self) -> Result<(), String> {
+        while self.eat("!") || self.eat("-") {}
+        self.member()
+    }
+
+    fn member(&mut self) -> Result<(), String> {
+        self.primary()?;
+
+        loop {
+            if self.eat(".") {
+                match self.advance() {
+                    Token::Ident(name) => {
+                        if self.eat("(") {
+                            self.call_args(COMPREHENSION_MACROS.contains(&name.as_str()))?;
+                        }
+                    }
+                    token => {
+                        self.pos -= 1;
+                        return Err(format!(
+                            "expected a field name after `.` but found {} at position {}",
+                            token,
+                            self.position()
+                        ));
+                    }
+                }
+            } else if self.eat("[") {
+                self.expr()?;
+                self.expect("]")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn primary(&mut self) -> Result<(), String> {
+        let position = self.position();
+        match self.advance() {
+            Token::Literal => Ok(()),
+            Token::Ident(name) => {
+                if self.eat("(") {
+                    return self.call_args(false);
+                }
+
+                if self.scope.contains(&name) || self.roots.contains(&name.as_str()) {
+                    return Ok(());
+                }
+
+                Err(format!(
+                    "unknown identifier `{}` at position {}; expressions can only reference {}",
+                    name,
+                    position,
+                    self.roots.join(", ")
+                ))
+            }
+            Token::Punct("(") => {
+                self.expr()?;
+                self.expect(")")
+            }
+            Token::Punct("[") => self.list("]", false),
+            Token::Punct("{") => self.list("}", true),
+            token => {
+                self.pos -= usize::from(token != Token::Eof);
+                Err(self.unexpected(token))
+            }
+        }
+    }
+
+    // Parses `expr (',' expr)* ','? close`, with `key: value` pairs for maps.
+    fn list(&mut self, close: &str, map: bool) -> Result<(), String> {
+        while !self.eat(close) {
+            self.expr()?;
+            if map {
+                self.expect(":")?;
+                self.expr()?;
+            }
+            if !self.eat(",") {
+                return self.expect(close);
+            }
+        }
+        Ok(())
+    }
+
+    // Parses call arguments after `(`. Comprehension macros such as
+    // `exists(x, x > 1)` bind their first argument as a local variable.
+    fn call_args(&mut self, comprehension: bool) -> Result<(), String> {
+        let next = self.tokens.get(self.pos + 1).map(|(token, _)| token);
+        let bound = match (&self.tokens[self.pos].0, next) {
+            (Token::Ident(name), Some(Token::Punct(","))) if comprehension => Some(name.clone()),
+            _ => None,
+        };
+
+        if let Some(name) = bound {
+            self.pos += 2;
+            self.scope.push(name);
+            let result = self.list(")", false);
+            self.scope.pop();
+            return result;
+        }
+
+        self.list(")", false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_common_expressions() {
+        for expr in [
+            "event.data.userId == async.data.userId",
+            "event.data.amount > 100 && event.data.currency in ['usd', \"eur\"]",
+            "has(event.data.plan) ? event.data.plan : 'free'",
+            "event.data.items.exists(item, item.price >= 1.5e2)",
+            "size(event.data.tags) > 0u || !event.data.active",
+            "event.data['user-id'] + '-' + string(event.ts % 60)",
+            "{'a': 1, 'b': [1, 2,]}.a == 1",
+            "r'raw\\d' == b\"bytes\"",
+        ] {
+            assert_eq!(validate_expression(expr), Ok(()), "{expr}");
+        }
+    }
+
+    #[test]
+    fn rejects_single_equals_with_a_hint() {
+        assert_eq!(
+            validate_expression("event.data.userId = async.data.userId"),
+            Err("unexpected character `=` at position 19, did you mean `==`?".to_string())
+        );
+        assert_eq!(
+            validate_expression("event.data.a & event.data.b"),
+            Err("unexpected character `&` at position 14, did you mean `&&`?".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_roots() {
+        assert_eq!(
+            validate_expression("user.id == event.data.id"),
+            Err(
+                "unknown identifier `user` at position 1; expressions can only reference event, async"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validate_with_roots("async.data.id", EVENT_ROOTS),
+            Err(
+                "unknown identifier `async` at position 1; expressions can only reference event"
+                    .to_string()
+            )
+        );
+        assert!(validate_expression("event.data.items.all(x, y > 1)").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_syntax() {
+        assert_eq!(
+            validate_expression("(event.data.a == 1"),
+            Err("expected `)` but found end of expression at position 19".to_string())
+        );
+        assert_eq!(
+            validate_expression("event.data.a =="),
+            Err("unexpected end of expression at position 16".to_string())
+        );
+        assert_eq!(
+            validate_expression("event.data.name == 'bob"),
+            Err("unterminated string starting at position 20".to_string())
+        );
+        assert_eq!(
+            validate_expression("event..data"),
+            Err("expected a field name after `.` but found `.` at position 7".to_string())
+        );
+        assert_eq!(
+            validate_expression("event.data.a 1"),
+            Err("unexpected literal at position 14".to_string())
+        );
+        assert_eq!(
+            validate_expression("  "),
+            Err("expression cannot be empty".to_string())
+        );
+    }
+}
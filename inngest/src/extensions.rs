@@ -0,0 +1,84 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+    sync::Arc,
+};
+
+/// Shared application state passed to every function run.
+///
+/// Values are registered once on the [`Handler`](crate::handler::Handler)
+/// with [`Handler::state`](crate::handler::Handler::state) and looked up by
+/// type from inside a function with
+/// [`Input::state`](crate::function::Input::state). One value is kept per
+/// type, so wrap values in a newtype to register two of the same type.
+///
+/// Cloning is cheap: clones share the stored values.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    values: Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+}
+
+impl Extensions {
+    /// Creates an empty set of extensions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `value`, replacing any previous value of the same type.
+    pub fn insert<T: Send + Sync + 'static>(&mut self, value: T) {
+        Arc::make_mut(&mut self.values).insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    /// Returns the value of type `T`, if one was registered.
+    pub fn get<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| Arc::clone(value).downcast::<T>().ok())
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl Debug for Extensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.values.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Pool(&'static str);
+
+    #[test]
+    fn get_returns_values_by_type() {
+        let mut extensions = Extensions::new();
+        extensions.insert(Pool("primary"));
+        extensions.insert(42_u32);
+
+        assert_eq!(extensions.get::<Pool>().as_deref(), Some(&Pool("primary")));
+        assert_eq!(extensions.get::<u32>().as_deref(), Some(&42));
+        assert!(extensions.get::<String>().is_none());
+    }
+
+    #[test]
+    fn insert_replaces_values_of_the_same_type() {
+        let mut extensions = Extensions::new();
+        extensions.insert(Pool("primary"));
+        extensions.insert(Pool("replica"));
+
+        assert_eq!(extensions.len(), 1);
+        assert_eq!(extensions.get::<Pool>().as_deref(), Some(&Pool("replica")));
+    }
+}
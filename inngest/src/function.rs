@@ -1,14 +1,32 @@
 use crate::{
+    basic_error,
     client::Inngest,
-    event::{Event, InngestEvent},
+    cron::Cron,
+    event::{Event, EventEnum, InngestEvent},
+    expression,
+    extensions::Extensions,
+    result::Error,
     step_tool::Step as StepTool,
     utils::duration,
 };
 use futures::{future::BoxFuture, FutureExt};
-use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize};
+use serde::{de::DeserializeOwned, de::Error as DeError, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 use slug::slugify;
-use std::{collections::HashMap, fmt::Debug, time::Duration};
+use std::{collections::HashMap, fmt::Debug, future::Future, sync::Arc, time::Duration};
+
+/// The maximum number of triggers a single function can declare.
+pub const MAX_TRIGGERS: usize = 10;
+
+/// The maximum number of retries a function can be configured with.
+pub const MAX_RETRIES: u8 = 20;
+
+const SECOND: Duration = Duration::from_secs(1);
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// The system event sent for cron-triggered runs.
+const SCHEDULED_TIMER_EVENT: &str = "inngest/scheduled.timer";
 
 // NOTE: should T have Copy trait too?
 // so it can do something like `input.event` without moving.
@@ -17,6 +35,20 @@ pub struct Input<T: 'static> {
     pub event: Event<T>,
     pub events: Vec<Event<T>>,
     pub ctx: InputCtx,
+    /// Shared state registered on the handler with
+    /// [`Handler::state`](crate::handler::Handler::state).
+    pub extensions: Extensions,
+}
+
+impl<T> Input<T> {
+    /// Returns the shared state of type `S` registered on the handler.
+    ///
+    /// ```ignore
+    /// let pool = input.state::<PgPool>().expect("pool should be registered");
+    /// ```
+    pub fn state<S: Send + Sync + 'static>(&self) -> Option<Arc<S>> {
+        self.extensions.get::<S>()
+    }
 }
 
 pub struct InputCtx {
@@ -25,6 +57,9 @@ pub struct InputCtx {
     pub run_id: String,
     pub step_id: String,
     pub attempt: u8,
+    /// The trigger that started this run, when it can be determined from the
+    /// triggering event.
+    pub trigger: Option<Trigger>,
 }
 
 /// Error information attached to an `inngest/function.failed` event.
@@ -51,6 +86,110 @@ pub struct FunctionFailureEvent<T: 'static> {
     pub run_id: String,
 }
 
+/// The payload carried by the `inngest/function.cancelled` system event.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FunctionCancelledEvent<T: 'static> {
+    pub event: Event<T>,
+    pub function_id: String,
+    pub run_id: String,
+    /// Details of the cancellation, when Inngest reports them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<FunctionFailureError>,
+}
+
+/// The payload carried by the `inngest/function.finished` system event.
+///
+/// Exactly one of `result` and `error` is set, depending on whether the run
+/// succeeded. `result` holds the function's typed output.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FunctionFinishedEvent<T: 'static, O: 'static = Value> {
+    pub event: Event<T>,
+    pub function_id: String,
+    pub run_id: String,
+    #[serde(default)]
+    pub result: Option<O>,
+    #[serde(default)]
+    pub error: Option<FunctionFailureError>,
+}
+
+impl<T, O> FunctionFinishedEvent<T, O> {
+    /// Returns the output of a successful run or the error of a failed one.
+    pub fn outcome(&self) -> Result<Option<&O>, &FunctionFailureError> {
+        match &self.error {
+            Some(error) => Err(error),
+            None => Ok(self.result.as_ref()),
+        }
+    }
+}
+
+/// The payload passed to an `on_success` handler: a
+/// [`FunctionFinishedEvent`] for a run that completed without error.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct FunctionSuccessEvent<T: 'static, O: 'static = Value> {
+    pub event: Event<T>,
+    pub function_id: String,
+    pub run_id: String,
+    pub result: O,
+}
+
+impl<T> FunctionFinishedEvent<T> {
+    fn into_typed<O: DeserializeOwned>(self) -> Result<FunctionFinishedEvent<T, O>, Error> {
+        let result = self
+            .result
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|err| basic_error!("error parsing function output: {}", err))?;
+
+        Ok(FunctionFinishedEvent {
+            event: self.event,
+            function_id: self.function_id,
+            run_id: self.run_id,
+            result,
+            error: self.error,
+        })
+    }
+
+    fn into_success<O: DeserializeOwned>(self) -> Result<FunctionSuccessEvent<T, O>, Error> {
+        let result = serde_json::from_value(self.result.unwrap_or(Value::Null))
+            .map_err(|err| basic_error!("error parsing function output: {}", err))?;
+
+        Ok(FunctionSuccessEvent {
+            event: self.event,
+            function_id: self.function_id,
+            run_id: self.run_id,
+            result,
+        })
+    }
+}
+
+/// Converts the payload of every event in a lifecycle handler's input.
+fn map_input<A, B>(
+    input: Input<A>,
+    convert: impl Fn(A) -> Result<B, Error>,
+) -> Result<Input<B>, Error> {
+    let map_event = |event: Event<A>| -> Result<Event<B>, Error> {
+        Ok(Event {
+            id: event.id,
+            name: event.name,
+            data: convert(event.data)?,
+            user: event.user,
+            timestamp: event.timestamp,
+            version: event.version,
+        })
+    };
+
+    Ok(Input {
+        event: map_event(input.event)?,
+        events: input
+            .events
+            .into_iter()
+            .map(map_event)
+            .collect::<Result<_, _>>()?,
+        ctx: input.ctx,
+        extensions: input.extensions,
+    })
+}
+
 /// A function-config time value accepted by the sync payload.
 ///
 /// Durations are serialized as Inngest time strings such as `5m` or `30s`.
@@ -63,13 +202,51 @@ pub enum FunctionTime {
 }
 
 impl FunctionTime {
-    fn validate(&self, field: &str) -> Result<(), String> {
+    pub(crate) fn validate(&self, field: &str) -> Result<(), String> {
         if matches!(self, Self::String(value) if value.trim().is_empty()) {
             return Err(format!("{field} cannot be empty"));
         }
 
         Ok(())
     }
+
+    /// Returns the value as a duration, if it is one or parses as one.
+    fn as_duration(&self) -> Option<Duration> {
+        match self {
+            Self::Duration(duration) => Some(*duration),
+            Self::String(value) => duration::parse(value),
+        }
+    }
+
+    /// Validates the value and, when it is a duration, that it falls within
+    /// `min..=max`. Expressions and timestamps are left to the server.
+    fn validate_range(
+        &self,
+        field: &str,
+        min: Duration,
+        max: Option<Duration>,
+    ) -> Result<(), String> {
+        self.validate(field)?;
+
+        let Some(value) = self.as_duration() else {
+            return Ok(());
+        };
+
+        match max {
+            Some(max) if value < min || value > max => Err(format!(
+                "{field} must be between {} and {}, got {}",
+                duration::to_string(min),
+                duration::to_string(max),
+                duration::to_string(value)
+            )),
+            None if value < min => Err(format!(
+                "{field} must be at least {}, got {}",
+                duration::to_string(min),
+                duration::to_string(value)
+            )),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl From<Duration> for FunctionTime {
@@ -426,11 +603,17 @@ impl FunctionTimeouts {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Retry attempts used when neither the function nor its app or group
+/// defaults configure retries.
+pub const DEFAULT_RETRIES: u8 = 3;
+
+#[derive(Debug, Clone, Default)]
 pub struct FunctionOpts {
     pub id: String,
     pub name: Option<String>,
-    pub retries: u8,
+    /// Retry attempts, or `None` to use the app default of
+    /// [`DEFAULT_RETRIES`].
+    pub retries: Option<u8>,
     pub cancel: Vec<FunctionCancel>,
     pub idempotency: Option<String>,
     pub batch_events: Option<FunctionBatchEvents>,
@@ -443,26 +626,6 @@ pub struct FunctionOpts {
     pub timeouts: Option<FunctionTimeouts>,
 }
 
-impl Default for FunctionOpts {
-    fn default() -> Self {
-        FunctionOpts {
-            id: String::new(),
-            name: None,
-            retries: 3,
-            cancel: Vec::new(),
-            idempotency: None,
-            batch_events: None,
-            rate_limit: None,
-            debounce: None,
-            priority: None,
-            concurrency: None,
-            throttle: None,
-            singleton: None,
-            timeouts: None,
-        }
-    }
-}
-
 impl FunctionOpts {
     /// Creates function options with the provided identifier.
     pub fn new(id: &str) -> Self {
@@ -480,7 +643,7 @@ impl FunctionOpts {
 
     /// Overrides the number of retry attempts the executor should schedule.
     pub fn retries(mut self, retries: u8) -> Self {
-        self.retries = retries;
+        self.retries = Some(retries);
         self
     }
 
@@ -492,8 +655,8 @@ impl FunctionOpts {
 
     /// Sets an idempotency expression for the function.
     ///
-    /// When present, the sync payload omits `rate_limit` to match the spec's
-    /// idempotency precedence rules.
+    /// Idempotency is a rate limit of one run per key every 24 hours, so it
+    /// cannot be combined with [`FunctionOpts::rate_limit`].
     pub fn idempotency(mut self, idempotency: &str) -> Self {
         self.idempotency = Some(idempotency.to_string());
         self
@@ -536,6 +699,9 @@ impl FunctionOpts {
     }
 
     /// Configures singleton execution for the function.
+    ///
+    /// Singleton functions cannot also use
+    /// [`FunctionOpts::batch_events`].
     pub fn singleton(mut self, singleton: FunctionSingleton) -> Self {
         self.singleton = Some(singleton);
         self
@@ -548,28 +714,107 @@ impl FunctionOpts {
     }
 }
 
-type Func<T, E> =
-    dyn Fn(Input<T>, StepTool) -> BoxFuture<'static, Result<Value, E>> + Send + Sync + 'static;
+/// Options shared by every function in an app or
+/// [`FunctionGroup`](crate::handler::FunctionGroup).
+///
+/// Defaults only fill in what a function leaves unset: a function's own
+/// retries, concurrency and timeouts win, and its cancel rules replace a
+/// default rule for the same event.
+#[derive(Debug, Clone, Default)]
+pub struct FunctionDefaults {
+    pub retries: Option<u8>,
+    pub timeouts: Option<FunctionTimeouts>,
+    pub concurrency: Option<FunctionConcurrency>,
+    pub cancel: Vec<FunctionCancel>,
+}
+
+impl FunctionDefaults {
+    /// Creates an empty set of defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the default number of retry attempts.
+    pub fn retries(mut self, retries: u8) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Sets default start and finish timeouts.
+    pub fn timeouts(mut self, timeouts: FunctionTimeouts) -> Self {
+        self.timeouts = Some(timeouts);
+        self
+    }
+
+    /// Sets the default concurrency configuration.
+    pub fn concurrency(mut self, concurrency: FunctionConcurrency) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// Adds a default cancellation rule.
+    pub fn cancel(mut self, cancel: FunctionCancel) -> Self {
+        self.cancel.push(cancel);
+        self
+    }
+
+    pub(crate) fn apply(&self, opts: &mut FunctionOpts) {
+        if opts.retries.is_none() {
+            opts.retries = self.retries;
+        }
+
+        if let Some(defaults) = &self.timeouts {
+            let timeouts = opts.timeouts.get_or_insert_with(FunctionTimeouts::default);
+            if timeouts.start.is_none() {
+                timeouts.start = defaults.start.clone();
+            }
+            if timeouts.finish.is_none() {
+                timeouts.finish = defaults.finish.clone();
+            }
+        }
+
+        if opts.concurrency.is_none() {
+            opts.concurrency = self.concurrency.clone();
+        }
+
+        for cancel in &self.cancel {
+            if !opts.cancel.iter().any(|own| own.event == cancel.event) {
+                opts.cancel.push(cancel.clone());
+            }
+        }
+    }
+}
+
+type Func<T, E, O = Value> =
+    dyn Fn(Input<T>, StepTool) -> BoxFuture<'static, Result<O, E>> + Send + Sync + 'static;
 
-pub struct ServableFn<T: 'static, E> {
+/// A function ready to be registered with a [`Handler`](crate::handler::Handler).
+///
+/// `O` is the function's output type. It is serialized to JSON when the run
+/// completes and defaults to [`Value`] for functions that return `json!(...)`.
+pub struct ServableFn<T: 'static, E, O = Value> {
     pub(crate) app_id: String,
     pub(crate) client: Inngest,
     pub opts: FunctionOpts,
-    pub trigger: Trigger,
-    pub func: Box<Func<T, E>>,
+    pub triggers: Vec<Trigger>,
+    pub func: Box<Func<T, E, O>>,
     pub(crate) on_failure: Option<Box<Func<FunctionFailureEvent<T>, E>>>,
+    pub(crate) on_cancel: Option<Box<Func<FunctionCancelledEvent<T>, E>>>,
+    pub(crate) on_finish: Option<Box<Func<FunctionFinishedEvent<T>, Error>>>,
+    pub(crate) on_success: Option<Box<Func<FunctionFinishedEvent<T>, Error>>>,
+    pub(crate) event_names: Option<&'static [&'static str]>,
 }
 
-impl<T: InngestEvent, E> Debug for ServableFn<T, E> {
+impl<T: InngestEvent, E, O> Debug for ServableFn<T, E, O> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ServableFn")
             .field("id", &self.opts.id)
-            .field("trigger", &self.trigger())
+            .field("triggers", &self.triggers)
             .finish()
     }
 }
 
-impl<T, E> ServableFn<T, E> {
+impl<T, E, O> ServableFn<T, E, O> {
     // TODO: prepend app_id
     pub fn slug(&self) -> String {
         format!("{}-{}", &self.app_id, slugify(self.opts.id.clone()))
@@ -582,8 +827,8 @@ impl<T, E> ServableFn<T, E> {
         }
     }
 
-    pub fn trigger(&self) -> Trigger {
-        self.trigger.clone()
+    pub fn triggers(&self) -> &[Trigger] {
+        &self.triggers
     }
 
     /// Registers a Rust-style `on_failure` handler for this function.
@@ -600,6 +845,90 @@ impl<T, E> ServableFn<T, E> {
         self
     }
 
+    /// Registers an `on_cancel` handler that runs when a run of this function
+    /// is cancelled, for example by a [`FunctionCancel`] rule, so half-done
+    /// side effects can be cleaned up.
+    ///
+    /// Like [`ServableFn::on_failure`], the handler is synced as a separate
+    /// internal function, triggered by the `inngest/function.cancelled` system
+    /// event for this function.
+    pub fn on_cancel<F: futures::Future<Output = Result<Value, E>> + Send + Sync + 'static>(
+        mut self,
+        func: impl Fn(Input<FunctionCancelledEvent<T>>, StepTool) -> F + Send + Sync + 'static,
+    ) -> Self {
+        self.on_cancel = Some(Box::new(move |input, step| func(input, step).boxed()));
+        self
+    }
+
+    /// Registers an `on_finish` handler that runs after every run of this
+    /// function, whether it succeeded or failed.
+    ///
+    /// Like [`ServableFn::on_failure`], the handler is synced as a separate
+    /// internal function, triggered by the `inngest/function.finished` system
+    /// event. The event carries the run's typed output or its error.
+    pub fn on_finish<F>(
+        mut self,
+        func: impl Fn(Input<FunctionFinishedEvent<T, O>>, StepTool) -> F + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send,
+        E: Into<Error> + 'static,
+        O: DeserializeOwned + Send + 'static,
+        F: futures::Future<Output = Result<Value, E>> + Send + Sync + 'static,
+    {
+        let func = Arc::new(func);
+        self.on_finish = Some(Box::new(move |input, step| {
+            let func = Arc::clone(&func);
+            async move {
+                let input = map_input(input, FunctionFinishedEvent::into_typed)?;
+                func(input, step).await.map_err(Into::into)
+            }
+            .boxed()
+        }));
+        self
+    }
+
+    /// Registers an `on_success` handler that runs after each successful run
+    /// of this function with its typed output.
+    ///
+    /// This is built on the same `inngest/function.finished` event as
+    /// [`ServableFn::on_finish`], with a trigger expression that only matches
+    /// runs that finished without an error.
+    pub fn on_success<F>(
+        mut self,
+        func: impl Fn(Input<FunctionSuccessEvent<T, O>>, StepTool) -> F + Send + Sync + 'static,
+    ) -> Self
+    where
+        T: Send,
+        E: Into<Error> + 'static,
+        O: DeserializeOwned + Send + 'static,
+        F: futures::Future<Output = Result<Value, E>> + Send + Sync + 'static,
+    {
+        let func = Arc::new(func);
+        self.on_success = Some(Box::new(move |input, step| {
+            let func = Arc::clone(&func);
+            async move {
+                let input = map_input(input, FunctionFinishedEvent::into_success)?;
+                func(input, step).await.map_err(Into::into)
+            }
+            .boxed()
+        }));
+        self
+    }
+
+    /// Deserializes the triggering events into the variant of `T` named after
+    /// each event, see [`EventEnum`].
+    ///
+    /// Runs for an event that is not listed in [`EventEnum::event_names`]
+    /// fail with an error naming the event.
+    pub fn dispatch_by_event_name(mut self) -> Self
+    where
+        T: EventEnum,
+    {
+        self.event_names = Some(T::event_names());
+        self
+    }
+
     pub fn function(&self, serve_origin: &str, serve_path: &str) -> Function {
         let id = format!("{}-{}", &self.app_id, slugify(self.opts.id.clone()));
         let name = match self.opts.name.clone() {
@@ -618,7 +947,7 @@ impl<T, E> ServableFn<T, E> {
                     method: "http".to_string(),
                 },
                 retries: StepRetry {
-                    attempts: self.opts.retries,
+                    attempts: self.opts.retries.unwrap_or(DEFAULT_RETRIES),
                 },
             },
         );
@@ -626,16 +955,12 @@ impl<T, E> ServableFn<T, E> {
         Function {
             id,
             name,
-            triggers: vec![self.trigger.clone()],
+            triggers: self.triggers.clone(),
             steps,
             cancel: self.opts.cancel.clone(),
             idempotency: self.opts.idempotency.clone(),
             batch_events: self.opts.batch_events.clone(),
-            rate_limit: if self.opts.idempotency.is_some() {
-                None
-            } else {
-                self.opts.rate_limit.clone()
-            },
+            rate_limit: self.opts.rate_limit.clone(),
             debounce: self.opts.debounce.clone(),
             priority: self.opts.priority.clone(),
             concurrency: self.opts.concurrency.clone(),
@@ -678,6 +1003,21 @@ impl Function {
     /// Validates supported function config shapes during sync payload
     /// construction.
     pub fn validate(&self) -> Result<(), String> {
+        if self.triggers.is_empty() || self.triggers.len() > MAX_TRIGGERS {
+            return Err(format!(
+                "function {} triggers must include between 1 and {} triggers, got {}",
+                self.id,
+                MAX_TRIGGERS,
+                self.triggers.len()
+            ));
+        }
+
+        for trigger in &self.triggers {
+            trigger.validate(&self.id)?;
+        }
+
+        self.validate_expressions()?;
+
         if let Some(batch_events) = &self.batch_events {
             if batch_events.max_size == 0 || batch_events.max_size > 100 {
                 return Err(format!(
@@ -716,41 +1056,117 @@ impl Function {
             }
         }
 
+        if self.idempotency.is_some() && self.rate_limit.is_some() {
+            return Err(format!(
+                "function {} idempotency cannot be combined with rateLimit; idempotency is already a rate limit of one run per key every 24h",
+                self.id
+            ));
+        }
+
+        for step in self.steps.values() {
+            if step.retries.attempts > MAX_RETRIES {
+                return Err(format!(
+                    "function {} retries must be between 0 and {}, got {}",
+                    self.id, MAX_RETRIES, step.retries.attempts
+                ));
+            }
+        }
+
         for cancel in &self.cancel {
+            if cancel.event.trim().is_empty() {
+                return Err(format!("function {} cancel.event cannot be empty", self.id));
+            }
+
             if let Some(timeout) = &cancel.timeout {
-                timeout.validate(&format!("function {} cancel.timeout", self.id))?;
+                timeout.validate_range(
+                    &format!("function {} cancel.timeout", self.id),
+                    SECOND,
+                    None,
+                )?;
             }
         }
 
         if let Some(rate_limit) = &self.rate_limit {
-            rate_limit
-                .period
-                .validate(&format!("function {} rateLimit.period", self.id))?;
+            if rate_limit.limit == 0 {
+                return Err(format!(
+                    "function {} rateLimit.limit must be at least 1",
+                    self.id
+                ));
+            }
+
+            rate_limit.period.validate_range(
+                &format!("function {} rateLimit.period", self.id),
+                SECOND,
+                Some(DAY),
+            )?;
         }
 
         if let Some(debounce) = &self.debounce {
-            debounce
-                .period
-                .validate(&format!("function {} debounce.period", self.id))?;
+            debounce.period.validate_range(
+                &format!("function {} debounce.period", self.id),
+                SECOND,
+                Some(WEEK),
+            )?;
 
             if let Some(timeout) = &debounce.timeout {
-                timeout.validate(&format!("function {} debounce.timeout", self.id))?;
+                timeout.validate_range(
+                    &format!("function {} debounce.timeout", self.id),
+                    SECOND,
+                    Some(WEEK),
+                )?;
+            }
+        }
+
+        if let Some(priority) = &self.priority {
+            if priority.run.is_none() {
+                return Err(format!("function {} priority.run is required", self.id));
             }
         }
 
         if let Some(throttle) = &self.throttle {
-            throttle
-                .period
-                .validate(&format!("function {} throttle.period", self.id))?;
+            if throttle.limit == 0 {
+                return Err(format!(
+                    "function {} throttle.limit must be at least 1",
+                    self.id
+                ));
+            }
+
+            if throttle.burst == Some(0) {
+                return Err(format!(
+                    "function {} throttle.burst must be at least 1",
+                    self.id
+                ));
+            }
+
+            throttle.period.validate_range(
+                &format!("function {} throttle.period", self.id),
+                SECOND,
+                Some(WEEK),
+            )?;
+        }
+
+        if self.singleton.is_some() && self.batch_events.is_some() {
+            return Err(format!(
+                "function {} singleton cannot be combined with batchEvents",
+                self.id
+            ));
         }
 
         if let Some(timeouts) = &self.timeouts {
             if let Some(start) = &timeouts.start {
-                start.validate(&format!("function {} timeouts.start", self.id))?;
+                start.validate_range(
+                    &format!("function {} timeouts.start", self.id),
+                    SECOND,
+                    None,
+                )?;
             }
 
             if let Some(finish) = &timeouts.finish {
-                finish.validate(&format!("function {} timeouts.finish", self.id))?;
+                finish.validate_range(
+                    &format!("function {} timeouts.finish", self.id),
+                    SECOND,
+                    None,
+                )?;
             }
         }
 
@@ -758,6 +1174,64 @@ impl Function {
     }
 }
 
+impl Function {
+    fn validate_expressions(&self) -> Result<(), String> {
+        let keys = [
+            ("idempotency", self.idempotency.as_deref()),
+            (
+                "batchEvents.key",
+                self.batch_events.as_ref().and_then(|b| b.key.as_deref()),
+            ),
+            (
+                "rateLimit.key",
+                self.rate_limit.as_ref().and_then(|r| r.key.as_deref()),
+            ),
+            (
+                "debounce.key",
+                self.debounce.as_ref().and_then(|d| d.key.as_deref()),
+            ),
+            (
+                "priority.run",
+                self.priority.as_ref().and_then(|p| p.run.as_deref()),
+            ),
+            (
+                "throttle.key",
+                self.throttle.as_ref().and_then(|t| t.key.as_deref()),
+            ),
+            (
+                "singleton.key",
+                self.singleton.as_ref().and_then(|s| s.key.as_deref()),
+            ),
+        ];
+
+        let mut exprs: Vec<(&str, &str, &[&str])> = keys
+            .into_iter()
+            .filter_map(|(field, expr)| expr.map(|expr| (field, expr, expression::EVENT_ROOTS)))
+            .collect();
+
+        if let Some(FunctionConcurrency::Keyed(options)) = &self.concurrency {
+            for key in options.iter().filter_map(|option| option.key.as_deref()) {
+                exprs.push(("concurrency.key", key, expression::EVENT_ROOTS));
+            }
+        }
+
+        for if_exp in self
+            .cancel
+            .iter()
+            .filter_map(|cancel| cancel.if_exp.as_deref())
+        {
+            exprs.push(("cancel.if", if_exp, expression::MATCH_ROOTS));
+        }
+
+        for (field, expr, roots) in exprs {
+            expression::validate_with_roots(expr, roots)
+                .map_err(|err| format!("function {} {}: {}", self.id, field, err))?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Step {
     pub id: String,
@@ -785,9 +1259,31 @@ pub enum Trigger {
         event: String,
         expression: Option<String>,
     },
-    CronTrigger {
-        cron: String,
-    },
+    CronTrigger(CronTrigger),
+}
+
+/// The schedule of a cron trigger, created with [`Trigger::cron`] or from a
+/// [`Cron`] builder.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CronTrigger {
+    cron: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    jitter: Option<FunctionTime>,
+    /// Set when [`Trigger::expr`] is called on a cron trigger so the mistake
+    /// is reported by [`Function::validate`].
+    #[serde(skip)]
+    expression: Option<String>,
+}
+
+impl CronTrigger {
+    /// The cron expression, including any `TZ=` prefix.
+    pub fn cron(&self) -> &str {
+        &self.cron
+    }
+
+    pub fn jitter(&self) -> Option<&FunctionTime> {
+        self.jitter.as_ref()
+    }
 }
 
 impl Trigger {
@@ -798,20 +1294,215 @@ impl Trigger {
         }
     }
 
-    #[allow(unused_variables)]
+    /// Adds a filtering expression to an event trigger.
+    ///
+    /// Cron triggers do not support expressions; the expression is kept so
+    /// the sync payload is rejected instead of silently dropping it.
     pub fn expr(&self, exp: &str) -> Self {
         match self {
-            Trigger::EventTrigger { event, expression } => Trigger::EventTrigger {
+            Trigger::EventTrigger { event, .. } => Trigger::EventTrigger {
                 event: event.clone(),
                 expression: Some(exp.to_string()),
             },
-            Trigger::CronTrigger { cron } => Trigger::CronTrigger { cron: cron.clone() },
+            Trigger::CronTrigger(cron) => Trigger::CronTrigger(CronTrigger {
+                expression: Some(exp.to_string()),
+                ..cron.clone()
+            }),
         }
     }
 
+    /// Creates a cron trigger from an expression such as `0 12 * * 5` or
+    /// `TZ=Europe/Paris 0 12 * * 5`. Use [`Cron`] to set a timezone or jitter
+    /// with a builder.
     pub fn cron(cron: &str) -> Self {
-        Trigger::CronTrigger {
+        Trigger::CronTrigger(CronTrigger {
             cron: cron.to_string(),
+            jitter: None,
+            expression: None,
+        })
+    }
+
+    fn validate(&self, function_id: &str) -> Result<(), String> {
+        if let Trigger::EventTrigger {
+            event,
+            expression: Some(exp),
+        } = self
+        {
+            expression::validate_with_roots(exp, expression::EVENT_ROOTS).map_err(|err| {
+                format!(
+                    "function {} trigger {} expression: {}",
+                    function_id, event, err
+                )
+            })?;
+        }
+
+        if let Trigger::CronTrigger(CronTrigger {
+            cron,
+            jitter,
+            expression,
+        }) = self
+        {
+            if expression.is_some() {
+                return Err(format!(
+                    "function {} cron trigger {} does not support expressions",
+                    function_id, cron
+                ));
+            }
+
+            let parsed = Cron::parse(cron).and_then(|parsed| {
+                let parsed = match jitter {
+                    Some(jitter) => parsed.jitter(jitter.clone()),
+                    None => parsed,
+                };
+                parsed.validate()
+            });
+            if let Err(err) = parsed {
+                return Err(format!(
+                    "function {} cron trigger {}: {}",
+                    function_id, cron, err
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds the trigger in `triggers` that matches an incoming event.
+    ///
+    /// Event triggers match by name, including `prefix/*` wildcards; trigger
+    /// expressions are evaluated by Inngest and are not re-checked here. Cron
+    /// runs match the cron trigger whose schedule is reported in the event.
+    pub(crate) fn find_fired<'a>(
+        triggers: &'a [Trigger],
+        event_name: &str,
+        event_data: &Value,
+    ) -> Option<&'a Trigger> {
+        if event_name == SCHEDULED_TIMER_EVENT {
+            let cron = event_data.get("cron").and_then(Value::as_str);
+
+            return triggers.iter().find(
+                |trigger| matches!(trigger, Trigger::CronTrigger(c) if Some(c.cron()) == cron),
+            );
+        }
+
+        triggers
+            .iter()
+            .find(|trigger| trigger.fires_for(event_name))
+    }
+
+    /// The event name this trigger is synced with: its event, which may be a
+    /// `prefix/*` wildcard, or the scheduled timer event for cron triggers.
+    pub(crate) fn event_name(&self) -> &str {
+        match self {
+            Trigger::EventTrigger { event, .. } => event,
+            Trigger::CronTrigger(_) => SCHEDULED_TIMER_EVENT,
+        }
+    }
+
+    /// Whether an event named `event_name` can start a run through this
+    /// trigger, ignoring expressions and cron schedules.
+    pub(crate) fn fires_for(&self, event_name: &str) -> bool {
+        let name = self.event_name();
+        match name.strip_suffix('*') {
+            Some(prefix) => event_name.starts_with(prefix),
+            None => name == event_name,
         }
     }
 }
+
+/// A function defined as a named type instead of a closure.
+///
+/// Implementors can hold their own dependencies, be stored and unit tested
+/// like any other type, and are registered directly with
+/// [`Handler::register_fn`](crate::handler::Handler::register_fn):
+///
+/// ```ignore
+/// struct SendWelcome {
+///     mailer: Mailer,
+/// }
+///
+/// impl InngestFunction for SendWelcome {
+///     type Event = UserSignup;
+///     type Output = Value;
+///     type Error = Error;
+///
+///     fn opts(&self) -> FunctionOpts {
+///         FunctionOpts::new("send-welcome")
+///     }
+///
+///     fn trigger(&self) -> impl IntoTriggers {
+///         Trigger::event("user/signup")
+///     }
+///
+///     async fn run(&self, input: Input<UserSignup>, step: Step) -> Result<Value, Error> {
+///         self.mailer.send(&input.event.data.email).await?;
+///         Ok(json!({ "sent": true }))
+///     }
+/// }
+///
+/// handler.register_fn(SendWelcome { mailer })?;
+/// ```
+///
+/// Use [`Inngest::function`] to turn one into a [`ServableFn`], for example to
+/// attach an `on_failure` handler.
+pub trait InngestFunction: Send + Sync + 'static {
+    /// The payload of the triggering event.
+    type Event: InngestEvent + Send;
+    /// The output of a run, serialized to JSON.
+    type Output: Serialize + Send + 'static;
+    type Error: Into<Error> + Send + 'static;
+
+    fn opts(&self) -> FunctionOpts;
+
+    /// The event or cron triggers, in any form accepted by
+    /// [`Inngest::create_function`].
+    fn trigger(&self) -> impl IntoTriggers;
+
+    fn run(
+        &self,
+        input: Input<Self::Event>,
+        step: StepTool,
+    ) -> impl Future<Output = Result<Self::Output, Self::Error>> + Send;
+}
+
+/// Converts one or more triggers into the list accepted by
+/// [`Inngest::create_function`].
+///
+/// Implemented for a single [`Trigger`], arrays and vectors of triggers.
+pub trait IntoTriggers {
+    fn into_triggers(self) -> Vec<Trigger>;
+}
+
+impl IntoTriggers for Trigger {
+    fn into_triggers(self) -> Vec<Trigger> {
+        vec![self]
+    }
+}
+
+impl From<Cron> for Trigger {
+    fn from(cron: Cron) -> Self {
+        Trigger::CronTrigger(CronTrigger {
+            cron: cron.expression(),
+            jitter: cron.jitter,
+            expression: None,
+        })
+    }
+}
+
+impl IntoTriggers for Cron {
+    fn into_triggers(self) -> Vec<Trigger> {
+        vec![self.into()]
+    }
+}
+
+impl IntoTriggers for Vec<Trigger> {
+    fn into_triggers(self) -> Vec<Trigger> {
+        self
+    }
+}
+
+impl<const N: usize> IntoTriggers for [Trigger; N] {
+    fn into_triggers(self) -> Vec<Trigger> {
+        self.into()
+    }
+}
@@ -1,4 +1,10 @@
-use std::{collections::HashMap, panic::AssertUnwindSafe, sync::Arc};
+use std::{
+    collections::HashMap,
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+    time::{Duration, SystemTime},
+};
 
 use futures::{future::BoxFuture, FutureExt};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
@@ -10,28 +16,53 @@ use slug::slugify;
 use crate::{
     basic_error,
     client::{self, Inngest},
-    config::Config,
+    config::ConfigKey,
     event::{Event, InngestEvent},
-    function::{Function, FunctionOpts, Input, InputCtx, ServableFn, Trigger},
+    extensions::Extensions,
+    function::{
+        Function, FunctionDefaults, FunctionFailureEvent, FunctionOpts, InngestFunction, Input,
+        InputCtx, ServableFn, Trigger, DEFAULT_RETRIES,
+    },
     header::{self, Headers},
-    result::{DevError, Error, FlowControlVariant, SdkResponse},
-    sdk::Request,
+    result::{DevError, Error, FlowControlVariant, RegisterError, SdkResponse},
+    sdk::{diff::SyncDiff, Request},
     signature::Signature,
     step_tool::Step as StepTool,
     version::{self, EXECUTION_VERSION},
 };
 
-type DynamicFn = dyn Fn(RunQueryParams, Value) -> BoxFuture<'static, Result<SdkResponse, Error>>
+type DynamicFn = dyn Fn(RunQueryParams, Value, Extensions) -> BoxFuture<'static, Result<SdkResponse, Error>>
     + Send
     + Sync
     + 'static;
-type RegisteredFunc<T, E> =
-    dyn Fn(Input<T>, StepTool) -> BoxFuture<'static, Result<Value, E>> + Send + Sync + 'static;
+type RegisteredFunc<T, E, O> =
+    dyn Fn(Input<T>, StepTool) -> BoxFuture<'static, Result<O, E>> + Send + Sync + 'static;
+
+/// The system event sent when a function run fails.
+const FUNCTION_FAILED_EVENT: &str = "inngest/function.failed";
+
+/// The function ID of the app-wide failure handler, before the app prefix.
+const ANY_FAILURE_FN_ID: &str = "on-any-failure";
+
+/// What an internal function was generated for.
+enum Generated {
+    /// A lifecycle hook such as `on_failure` on the function with this
+    /// user-facing ID.
+    Hook { hook: &'static str, parent: String },
+    /// The handler registered with [`Handler::on_any_failure`].
+    AnyFailure,
+}
 
 struct DynamicServableFn {
     app_id: String,
     opts: FunctionOpts,
-    trigger: Trigger,
+    generated_for: Option<Generated>,
+    /// The payload enum's event names, for functions dispatching by event
+    /// name.
+    event_names: Option<&'static [&'static str]>,
+    /// Defaults of the [`FunctionGroup`] the function was registered with.
+    group_defaults: Option<FunctionDefaults>,
+    triggers: Vec<Trigger>,
     func: Box<DynamicFn>,
 }
 
@@ -40,9 +71,73 @@ impl DynamicServableFn {
         format!("{}-{}", &self.app_id, slugify(self.opts.id.clone()))
     }
 
-    fn function(&self, serve_origin: &str, serve_path: &str) -> Function {
+    fn describe(&self) -> String {
+        match &self.generated_for {
+            Some(Generated::Hook { hook, parent }) => {
+                format!("the {} handler of function {:?}", hook, parent)
+            }
+            Some(Generated::AnyFailure) => "the app's on_any_failure handler".to_string(),
+            None => format!("function {:?}", self.opts.id),
+        }
+    }
+
+    /// Checks that the payload enum's event names and the function's
+    /// triggers cover each other, so no triggered event fails to dispatch
+    /// and no variant is unreachable.
+    fn check_event_names(&self) -> Result<(), String> {
+        let Some(event_names) = self.event_names else {
+            return Ok(());
+        };
+
+        if let Some(trigger) = self
+            .triggers
+            .iter()
+            .find(|trigger| !event_names.iter().any(|name| trigger.fires_for(name)))
+        {
+            return Err(format!(
+                "function {} is triggered by {}, which is not listed in its event names",
+                self.slug(),
+                trigger.event_name()
+            ));
+        }
+
+        if let Some(name) = event_names
+            .iter()
+            .find(|name| !self.triggers.iter().any(|trigger| trigger.fires_for(name)))
+        {
+            return Err(format!(
+                "function {} lists event name {} but has no trigger for it",
+                self.slug(),
+                name
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the function's options with its group defaults and then the
+    /// app `defaults` filled in. Generated handlers such as `on_failure` keep
+    /// their own policy.
+    fn resolved_opts(&self, defaults: &FunctionDefaults) -> FunctionOpts {
+        let mut opts = self.opts.clone();
+        if self.generated_for.is_none() {
+            if let Some(group) = &self.group_defaults {
+                group.apply(&mut opts);
+            }
+            defaults.apply(&mut opts);
+        }
+        opts
+    }
+
+    fn function(
+        &self,
+        serve_origin: &str,
+        serve_path: &str,
+        defaults: &FunctionDefaults,
+    ) -> Function {
         let id = self.slug();
-        let name = match self.opts.name.clone() {
+        let opts = self.resolved_opts(defaults);
+        let name = match opts.name.clone() {
             Some(name) => name,
             None => id.clone(),
         };
@@ -58,7 +153,7 @@ impl DynamicServableFn {
                     method: "http".to_string(),
                 },
                 retries: crate::function::StepRetry {
-                    attempts: self.opts.retries,
+                    attempts: opts.retries.unwrap_or(DEFAULT_RETRIES),
                 },
             },
         );
@@ -66,27 +161,28 @@ impl DynamicServableFn {
         Function {
             id,
             name,
-            triggers: vec![self.trigger.clone()],
+            triggers: self.triggers.clone(),
             steps,
-            cancel: self.opts.cancel.clone(),
-            idempotency: self.opts.idempotency.clone(),
-            batch_events: self.opts.batch_events.clone(),
-            rate_limit: if self.opts.idempotency.is_some() {
-                None
-            } else {
-                self.opts.rate_limit.clone()
-            },
-            debounce: self.opts.debounce.clone(),
-            priority: self.opts.priority.clone(),
-            concurrency: self.opts.concurrency.clone(),
-            throttle: self.opts.throttle.clone(),
-            singleton: self.opts.singleton.clone(),
-            timeouts: self.opts.timeouts.clone(),
+            cancel: opts.cancel,
+            idempotency: opts.idempotency,
+            batch_events: opts.batch_events,
+            rate_limit: opts.rate_limit,
+            debounce: opts.debounce,
+            priority: opts.priority,
+            concurrency: opts.concurrency,
+            throttle: opts.throttle,
+            singleton: opts.singleton,
+            timeouts: opts.timeouts,
         }
     }
 
-    async fn run(&self, query: RunQueryParams, body: Value) -> Result<SdkResponse, Error> {
-        (self.func)(query, body).await
+    async fn run(
+        &self,
+        query: RunQueryParams,
+        body: Value,
+        extensions: Extensions,
+    ) -> Result<SdkResponse, Error> {
+        (self.func)(query, body, extensions).await
     }
 }
 
@@ -102,29 +198,60 @@ impl RegisteredFn {
     }
 }
 
-fn make_dynamic_fn<T, E>(
+/// A function that can be passed to [`Handler::register_fn`]: either a
+/// [`ServableFn`] built with [`Inngest::create_function`], or a type
+/// implementing [`InngestFunction`].
+pub trait IntoRegisteredFn {
+    fn into_registered_fn(self, client: &Inngest) -> RegisteredFn;
+}
+
+impl<T, E, O> IntoRegisteredFn for ServableFn<T, E, O>
+where
+    T: InngestEvent + Send,
+    E: Into<Error> + 'static,
+    O: Serialize + 'static,
+{
+    fn into_registered_fn(self, _client: &Inngest) -> RegisteredFn {
+        self.into()
+    }
+}
+
+impl<F: InngestFunction> IntoRegisteredFn for F {
+    fn into_registered_fn(self, client: &Inngest) -> RegisteredFn {
+        client.function(self).into()
+    }
+}
+
+fn make_dynamic_fn<T, E, O>(
     app_id: String,
     client: Inngest,
     opts: FunctionOpts,
-    trigger: Trigger,
-    func: Box<RegisteredFunc<T, E>>,
+    triggers: Vec<Trigger>,
+    event_names: Option<&'static [&'static str]>,
+    func: Box<RegisteredFunc<T, E, O>>,
 ) -> DynamicServableFn
 where
     T: InngestEvent + Send,
     E: Into<Error> + 'static,
+    O: Serialize + 'static,
 {
     let func = Arc::new(func);
+    let fn_triggers = Arc::new(triggers.clone());
 
     DynamicServableFn {
         app_id,
         opts,
-        trigger,
-        func: Box::new(move |query, body| {
+        generated_for: None,
+        event_names,
+        group_defaults: None,
+        triggers,
+        func: Box::new(move |query, body, extensions| {
             let step_func = Arc::clone(&func);
             let client = client.clone();
+            let fn_triggers = Arc::clone(&fn_triggers);
 
             async move {
-                let data = match serde_json::from_value::<RunRequestBody<T>>(body.clone()) {
+                let data = match RunRequestBody::<T>::parse(&body, event_names) {
                     Ok(res) => res,
                     Err(err) => {
                         println!("ERROR: {:?}", err);
@@ -134,6 +261,10 @@ where
                     }
                 };
 
+                let trigger =
+                    Trigger::find_fired(&fn_triggers, &data.event.name, &body["event"]["data"])
+                        .cloned();
+
                 let input = Input {
                     event: data.event,
                     events: data.events,
@@ -143,7 +274,9 @@ where
                         run_id: data.ctx.run_id.clone(),
                         step_id: query.step_id.clone(),
                         attempt: data.ctx.attempt,
+                        trigger,
                     },
+                    extensions,
                 };
 
                 let step_tool = StepTool::new_with_execution_mode(
@@ -173,10 +306,13 @@ where
                                     });
                                 }
 
-                                Ok(SdkResponse {
-                                    status: 200,
-                                    body: v,
-                                })
+                                match serde_json::to_value(v) {
+                                    Ok(body) => Ok(SdkResponse { status: 200, body }),
+                                    Err(err) => Err(basic_error!(
+                                        "error serializing function output: {}",
+                                        err
+                                    )),
+                                }
                             }
                             Err(err) => match err.into() {
                                 Error::Interrupt(mut flow) => {
@@ -231,51 +367,139 @@ where
     }
 }
 
-impl<T, E> From<ServableFn<T, E>> for RegisteredFn
+impl<T, E, O> From<ServableFn<T, E, O>> for RegisteredFn
 where
     T: InngestEvent + Send,
     E: Into<Error> + 'static,
+    O: Serialize + 'static,
 {
-    fn from(func: ServableFn<T, E>) -> Self {
+    fn from(func: ServableFn<T, E, O>) -> Self {
         let ServableFn {
             app_id,
             client,
             opts,
-            trigger,
+            triggers,
             func,
             on_failure,
+            on_cancel,
+            on_finish,
+            on_success,
+            event_names,
         } = func;
 
-        let function_id = format!("{}-{}", app_id, slugify(opts.id.clone()));
-        let function_name = opts.name.clone().unwrap_or_else(|| function_id.clone());
-
         let mut funcs = vec![make_dynamic_fn(
             app_id.clone(),
             client.clone(),
             opts.clone(),
-            trigger,
+            triggers,
+            event_names,
             func,
         )];
 
+        let parent = LifecycleParent {
+            app_id: &app_id,
+            client: &client,
+            opts: &opts,
+        };
         if let Some(on_failure) = on_failure {
-            let failure_opts = FunctionOpts::new(&format!("{}-failure", opts.id))
-                .name(&format!("{} (failure)", function_name))
-                .retries(0);
-
-            funcs.push(make_dynamic_fn(
-                app_id,
-                client,
-                failure_opts,
-                Trigger::event("inngest/function.failed")
-                    .expr(&format!("event.data.function_id == \"{}\"", function_id)),
+            funcs.push(parent.hook_fn(
+                "on_failure",
+                "failure",
+                FUNCTION_FAILED_EVENT,
+                None,
                 on_failure,
             ));
         }
+        if let Some(on_cancel) = on_cancel {
+            funcs.push(parent.hook_fn(
+                "on_cancel",
+                "cancel",
+                "inngest/function.cancelled",
+                None,
+                on_cancel,
+            ));
+        }
+        if let Some(on_finish) = on_finish {
+            funcs.push(parent.hook_fn(
+                "on_finish",
+                "finish",
+                "inngest/function.finished",
+                None,
+                on_finish,
+            ));
+        }
+        if let Some(on_success) = on_success {
+            funcs.push(parent.hook_fn(
+                "on_success",
+                "success",
+                "inngest/function.finished",
+                Some(SUCCEEDED_CONDITION),
+                on_success,
+            ));
+        }
 
         Self(funcs)
     }
 }
 
+/// Narrows `inngest/function.finished` to runs that did not fail, so failed
+/// runs never start an `on_success` handler.
+const SUCCEEDED_CONDITION: &str = "!has(event.data.error)";
+
+/// The function a lifecycle handler such as `on_failure` is generated for.
+struct LifecycleParent<'a> {
+    app_id: &'a str,
+    client: &'a Inngest,
+    opts: &'a FunctionOpts,
+}
+
+impl LifecycleParent<'_> {
+    /// Builds the internal function that runs `func` whenever the parent
+    /// function emits the system event `event`, optionally narrowed further
+    /// by the expression `condition`.
+    fn hook_fn<T, E>(
+        &self,
+        hook: &'static str,
+        suffix: &str,
+        event: &str,
+        condition: Option<&str>,
+        func: Box<RegisteredFunc<T, E, Value>>,
+    ) -> DynamicServableFn
+    where
+        T: InngestEvent + Send,
+        E: Into<Error> + 'static,
+    {
+        let function_id = format!("{}-{}", self.app_id, slugify(self.opts.id.clone()));
+        let function_name = self
+            .opts
+            .name
+            .clone()
+            .unwrap_or_else(|| function_id.clone());
+        let opts = FunctionOpts::new(&format!("{}-{}", self.opts.id, suffix))
+            .name(&format!("{} ({})", function_name, suffix))
+            .retries(0);
+
+        let mut expr = format!("event.data.function_id == \"{}\"", function_id);
+        if let Some(condition) = condition {
+            expr = format!("{} && {}", expr, condition);
+        }
+
+        let mut hook_fn = make_dynamic_fn(
+            self.app_id.to_string(),
+            self.client.clone(),
+            opts,
+            vec![Trigger::event(event).expr(&expr)],
+            None,
+            func,
+        );
+        hook_fn.generated_for = Some(Generated::Hook {
+            hook,
+            parent: self.opts.id.clone(),
+        });
+        hook_fn
+    }
+}
+
 /// An Inngest app handler that serves registered functions over HTTP.
 ///
 /// A single handler can register functions with different event payload types.
@@ -288,8 +512,94 @@ pub struct Handler {
     signing_key_fallback: Option<String>,
     serve_origin: Option<String>,
     serve_path: Option<String>,
-    funcs: HashMap<String, DynamicServableFn>,
+    funcs: RwLock<HashMap<String, Arc<DynamicServableFn>>>,
     mode: Kind,
+    strict_ids: bool,
+    defaults: FunctionDefaults,
+    extensions: Extensions,
+    sync_state: Mutex<SyncState>,
+    /// Held while a sync payload is built and sent, so syncs started by quick
+    /// successive function changes cannot land out of order.
+    sync_lock: tokio::sync::Mutex<()>,
+    sync_retries: u32,
+    sync_retry_backoff: Duration,
+}
+
+/// The framework reported by syncs that are not started by a request, which
+/// matches the bundled [`serve::axum`](crate::serve::axum) handlers.
+const DEFAULT_FRAMEWORK: &str = "axum";
+
+/// Default number of retries for [`Handler::sync_on_start`].
+const DEFAULT_SYNC_RETRIES: u32 = 8;
+/// Default delay before the first [`Handler::sync_on_start`] retry.
+const DEFAULT_SYNC_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+/// The longest delay between [`Handler::sync_on_start`] retries.
+const MAX_SYNC_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Where the app was synced to, reused to re-sync when functions are added or
+/// removed at runtime.
+#[derive(Clone)]
+struct SyncTarget {
+    serve_origin: String,
+    serve_path: String,
+    framework: String,
+}
+
+struct SyncState {
+    /// The target of the last successful sync.
+    target: Option<SyncTarget>,
+    status: SyncStatus,
+}
+
+/// The outcome of the handler's most recent sync, for health checks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyncStatus {
+    /// No sync has finished yet.
+    NotSynced,
+    /// The last sync succeeded.
+    Synced { at: SystemTime, modified: bool },
+    /// The last sync failed. `attempts` counts consecutive failures.
+    Failed {
+        at: SystemTime,
+        attempts: u32,
+        error: String,
+    },
+}
+
+impl SyncStatus {
+    pub fn is_synced(&self) -> bool {
+        matches!(self, Self::Synced { .. })
+    }
+}
+
+/// A set of functions registered with shared [`FunctionDefaults`].
+///
+/// Created with [`Handler::group`]. Group defaults take precedence over the
+/// app defaults set with [`Handler::defaults`], and each function's own
+/// options take precedence over both.
+pub struct FunctionGroup<'a> {
+    handler: &'a mut Handler,
+    defaults: FunctionDefaults,
+}
+
+impl FunctionGroup<'_> {
+    /// Registers a single function with the group defaults applied.
+    pub fn register_fn(&mut self, func: impl IntoRegisteredFn) -> Result<(), RegisterError> {
+        let func = func.into_registered_fn(&self.handler.inngest);
+        self.register_fns([func])
+    }
+
+    /// Registers multiple functions with the group defaults applied.
+    pub fn register_fns<I>(&mut self, funcs: I) -> Result<(), RegisterError>
+    where
+        I: IntoIterator<Item = RegisteredFn>,
+    {
+        let funcs = funcs
+            .into_iter()
+            .flat_map(RegisteredFn::into_dynamics)
+            .collect();
+        self.handler.insert_fns(funcs, Some(&self.defaults))
+    }
 }
 
 #[derive(Clone, Deserialize)]
@@ -300,7 +610,7 @@ pub struct RunQueryParams {
     step_id: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Default)]
 pub struct SyncQueryParams {
     #[serde(rename = "deployId")]
     deploy_id: Option<String>,
@@ -313,10 +623,13 @@ fn default_step_id() -> String {
 impl Handler {
     /// Creates a new handler for the given Inngest client.
     pub fn new(client: &Inngest) -> Self {
-        let signing_key = Config::signing_key();
-        let signing_key_fallback = Config::signing_key_fallback();
-        let serve_origin = Config::serve_origin();
-        let serve_path = Config::serve_path();
+        let signing_key = client.signing_key.clone();
+        let signing_key_fallback = client.signing_key_fallback.clone();
+        let serve_origin = client
+            .config()
+            .get(ConfigKey::ServeOrigin)
+            .map(String::from);
+        let serve_path = client.config().get(ConfigKey::ServePath).map(String::from);
         let mode = client.mode();
 
         Handler {
@@ -325,8 +638,18 @@ impl Handler {
             serve_origin,
             serve_path,
             inngest: client.clone(),
-            funcs: HashMap::new(),
+            funcs: RwLock::new(HashMap::new()),
             mode,
+            strict_ids: false,
+            defaults: FunctionDefaults::default(),
+            extensions: Extensions::default(),
+            sync_state: Mutex::new(SyncState {
+                target: None,
+                status: SyncStatus::NotSynced,
+            }),
+            sync_lock: tokio::sync::Mutex::new(()),
+            sync_retries: DEFAULT_SYNC_RETRIES,
+            sync_retry_backoff: DEFAULT_SYNC_RETRY_BACKOFF,
         }
     }
 
@@ -354,19 +677,106 @@ impl Handler {
         self
     }
 
-    /// Registers a single function with the handler.
+    /// Sets how many times [`Handler::sync_on_start`] retries a failed sync.
+    pub fn sync_retries(mut self, retries: u32) -> Self {
+        self.sync_retries = retries;
+        self
+    }
+
+    /// Sets the delay before the first [`Handler::sync_on_start`] retry. The
+    /// delay doubles after each attempt, up to one minute.
+    pub fn sync_retry_backoff(mut self, backoff: Duration) -> Self {
+        self.sync_retry_backoff = backoff;
+        self
+    }
+
+    /// Rejects function IDs that change when slugified, such as
+    /// `"Send Email"`, so synced IDs always match the IDs used in code.
+    pub fn strict_ids(mut self, strict: bool) -> Self {
+        self.strict_ids = strict;
+        self
+    }
+
+    /// Registers shared state that every function run can read with
+    /// [`Input::state`], such as database pools or API clients.
     ///
-    /// This is the simplest option when adding one function at a time,
-    /// regardless of its event payload type.
-    pub fn register_fn<T, E>(&mut self, func: ServableFn<T, E>)
+    /// One value is kept per type; registering another value of the same type
+    /// replaces it.
+    pub fn state<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        self.extensions.insert(value);
+        self
+    }
+
+    /// Sets app-level defaults applied to every registered function when the
+    /// app syncs, including functions registered before this call.
+    pub fn defaults(mut self, defaults: FunctionDefaults) -> Self {
+        self.defaults = defaults;
+        self
+    }
+
+    /// Starts a group of functions that share `defaults` on top of the app
+    /// defaults.
+    ///
+    /// ```ignore
+    /// handler
+    ///     .group(FunctionDefaults::new().retries(0))
+    ///     .register_fns(vec![cleanup_fn(&client).into()])?;
+    /// ```
+    pub fn group(&mut self, defaults: FunctionDefaults) -> FunctionGroup<'_> {
+        FunctionGroup {
+            handler: self,
+            defaults,
+        }
+    }
+
+    /// Registers one internal function that runs whenever any function in
+    /// this app fails, for app-wide alerting.
+    ///
+    /// The handler is triggered by the `inngest/function.failed` system event.
+    /// Its filter lists every function registered when the app syncs, so
+    /// functions added later are covered too. Lifecycle handlers such as
+    /// [`ServableFn::on_failure`] are left out. The original event is passed as
+    /// [`Value`] since the failing function can have any payload type, and
+    /// `function_id` identifies which function failed. Like
+    /// [`ServableFn::on_failure`] handlers, it is not retried.
+    pub fn on_any_failure<E, F>(
+        &mut self,
+        func: impl Fn(Input<FunctionFailureEvent<Value>>, StepTool) -> F + Send + Sync + 'static,
+    ) -> Result<(), RegisterError>
     where
-        T: InngestEvent + Send,
         E: Into<Error> + 'static,
+        F: Future<Output = Result<Value, E>> + Send + Sync + 'static,
     {
-        let funcs = RegisteredFn::from(func).into_dynamics();
-        for func in funcs {
-            self.funcs.insert(func.slug(), func);
-        }
+        let app_id = self.inngest.app_id();
+        let opts = FunctionOpts::new(ANY_FAILURE_FN_ID)
+            .name(&format!("{} (any failure)", app_id))
+            .retries(0);
+
+        let mut any_failure = make_dynamic_fn(
+            app_id,
+            self.inngest.clone(),
+            opts,
+            vec![Trigger::event(FUNCTION_FAILED_EVENT)],
+            None,
+            Box::new(move |input, step| func(input, step).boxed()),
+        );
+        any_failure.generated_for = Some(Generated::AnyFailure);
+
+        self.insert_fns(vec![any_failure], None)
+    }
+
+    /// Registers a single function with the handler.
+    ///
+    /// This is the simplest option when adding one function at a time,
+    /// regardless of its event payload type. Registration fails without
+    /// changing the handler if the function's ID, or the ID of its generated
+    /// `on_failure` handler, collides with an already registered function.
+    ///
+    /// Both closures created with [`Inngest::create_function`] and types
+    /// implementing [`InngestFunction`] are accepted.
+    pub fn register_fn(&mut self, func: impl IntoRegisteredFn) -> Result<(), RegisterError> {
+        let func = func.into_registered_fn(&self.inngest);
+        self.insert_fns(func.into_dynamics(), None)
     }
 
     /// Registers multiple functions with the handler.
@@ -378,17 +788,267 @@ impl Handler {
     /// handler.register_fns(vec![
     ///     hello_fn(&client).into(),
     ///     step_run_fn(&client).into(),
-    /// ]);
+    /// ])?;
     /// ```
-    pub fn register_fns<I>(&mut self, funcs: I)
+    ///
+    /// Either every function is registered or, on an ID collision, none are.
+    pub fn register_fns<I>(&mut self, funcs: I) -> Result<(), RegisterError>
+    where
+        I: IntoIterator<Item = RegisteredFn>,
+    {
+        self.insert_fns(
+            funcs
+                .into_iter()
+                .flat_map(RegisteredFn::into_dynamics)
+                .collect(),
+            None,
+        )
+    }
+
+    /// Registers a function on a live handler, for example one shared as
+    /// `Arc<Handler>` with the HTTP server.
+    ///
+    /// If the app has already synced, a re-sync is started in the background
+    /// so Inngest picks up the change.
+    pub fn add_fn(self: &Arc<Self>, func: impl IntoRegisteredFn) -> Result<(), RegisterError> {
+        let func = func.into_registered_fn(&self.inngest);
+        self.add_fns([func])
+    }
+
+    /// Registers multiple functions on a live handler and re-syncs once.
+    ///
+    /// See [`Handler::add_fn`].
+    pub fn add_fns<I>(self: &Arc<Self>, funcs: I) -> Result<(), RegisterError>
     where
         I: IntoIterator<Item = RegisteredFn>,
     {
-        for f in funcs {
-            for func in f.into_dynamics() {
-                self.funcs.insert(func.slug(), func);
+        self.insert_fns(
+            funcs
+                .into_iter()
+                .flat_map(RegisteredFn::into_dynamics)
+                .collect(),
+            None,
+        )?;
+        self.resync_in_background();
+        Ok(())
+    }
+
+    /// Removes a function, along with its lifecycle handlers such as
+    /// `on_failure`, from a live handler.
+    ///
+    /// `id` is the ID the function was created with. Returns whether anything
+    /// was removed; if so and the app has already synced, a re-sync is started
+    /// in the background. Runs already in progress for the function fail on
+    /// their next step once it is removed.
+    pub fn remove_fn(self: &Arc<Self>, id: &str) -> bool {
+        let id = slugify(id);
+        let slug = format!("{}-{}", self.inngest.app_id(), id);
+
+        let removed = {
+            let mut funcs = self.funcs_mut();
+            let before = funcs.len();
+            funcs.retain(|key, func| {
+                *key != slug
+                    && !matches!(
+                        &func.generated_for,
+                        Some(Generated::Hook { parent, .. }) if slugify(parent) == id
+                    )
+            });
+            funcs.len() != before
+        };
+
+        if removed {
+            self.resync_in_background();
+        }
+        removed
+    }
+
+    /// Re-syncs the app with the details of its last successful sync.
+    ///
+    /// Returns `None` when the app has not synced yet, since the first sync
+    /// will include every registered function anyway.
+    pub async fn resync(&self) -> Option<Result<SyncResponse, String>> {
+        let target = self.sync_state().target.clone()?;
+
+        Some(
+            self.sync_to(&target, &SyncQueryParams::default())
+                .await
+                .map_err(|err| err.to_string()),
+        )
+    }
+
+    /// Syncs the app with Inngest in the background as soon as it starts, so
+    /// no `PUT` to the serve path is needed after a deploy.
+    ///
+    /// `public_url` is the URL Inngest uses to reach the serve path, such as
+    /// `https://example.com/api/inngest`. A URL without a path uses the
+    /// configured serve path. Network failures and `5xx` responses are
+    /// retried with exponential backoff, see [`Handler::sync_retries`]; other
+    /// failures such as an invalid signing key or function config fail
+    /// immediately. Every outcome is logged and reported by
+    /// [`Handler::sync_status`]. Must be called from within a Tokio runtime.
+    pub fn sync_on_start(
+        self: &Arc<Self>,
+        public_url: &str,
+    ) -> tokio::task::JoinHandle<Result<SyncResponse, String>> {
+        let handler = Arc::clone(self);
+        let public_url = public_url.to_string();
+
+        tokio::spawn(async move {
+            let target = match handler.start_sync_target(&public_url) {
+                Ok(target) => target,
+                Err(err) => {
+                    handler.record_sync_failure(&err);
+                    println!("ERROR: sync on start failed: {}", err);
+                    return Err(err);
+                }
+            };
+
+            let mut backoff = handler.sync_retry_backoff;
+            let mut attempt = 0;
+            loop {
+                match handler.sync_to(&target, &SyncQueryParams::default()).await {
+                    Ok(res) => {
+                        println!(
+                            "Synced app {} with Inngest at {}{}",
+                            handler.inngest.app_id(),
+                            target.serve_origin,
+                            target.serve_path
+                        );
+                        return Ok(res);
+                    }
+                    Err(err) if err.is_retryable() && attempt < handler.sync_retries => {
+                        attempt += 1;
+                        println!(
+                            "ERROR: sync on start failed, retrying in {:?} ({}/{}): {}",
+                            backoff, attempt, handler.sync_retries, err
+                        );
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_SYNC_RETRY_BACKOFF);
+                    }
+                    Err(err) => {
+                        println!(
+                            "ERROR: sync on start failed after {} attempts: {}",
+                            attempt + 1,
+                            err
+                        );
+                        return Err(err.to_string());
+                    }
+                }
+            }
+        })
+    }
+
+    /// Returns the outcome of the most recent sync.
+    pub fn sync_status(&self) -> SyncStatus {
+        self.sync_state().status.clone()
+    }
+
+    fn start_sync_target(&self, public_url: &str) -> Result<SyncTarget, String> {
+        let url = url::Url::parse(public_url)
+            .map_err(|err| format!("invalid public URL {:?}: {}", public_url, err))?;
+        if !url.has_host() {
+            return Err(format!("invalid public URL {:?}: missing host", public_url));
+        }
+
+        let serve_path = match url.path() {
+            "" | "/" => self.app_serve_path(),
+            path => path.to_string(),
+        };
+
+        Ok(SyncTarget {
+            serve_origin: url.origin().ascii_serialization(),
+            serve_path,
+            framework: DEFAULT_FRAMEWORK.to_string(),
+        })
+    }
+
+    fn sync_state(&self) -> std::sync::MutexGuard<'_, SyncState> {
+        self.sync_state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn record_sync_failure(&self, error: &str) {
+        let mut state = self.sync_state();
+        let attempts = match &state.status {
+            SyncStatus::Failed { attempts, .. } => attempts + 1,
+            _ => 1,
+        };
+        state.status = SyncStatus::Failed {
+            at: SystemTime::now(),
+            attempts,
+            error: error.to_string(),
+        };
+    }
+
+    /// Re-syncs run one at a time and read the registered functions once
+    /// they start, so the last one to run always reflects the latest change.
+    fn resync_in_background(self: &Arc<Self>) {
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+
+        let handler = Arc::clone(self);
+        runtime.spawn(async move {
+            if let Some(Err(err)) = handler.resync().await {
+                println!("ERROR: re-sync after function change failed: {}", err);
+            }
+        });
+    }
+
+    fn funcs(&self) -> RwLockReadGuard<'_, HashMap<String, Arc<DynamicServableFn>>> {
+        self.funcs.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn funcs_mut(&self) -> RwLockWriteGuard<'_, HashMap<String, Arc<DynamicServableFn>>> {
+        self.funcs.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn insert_fns(
+        &self,
+        mut funcs: Vec<DynamicServableFn>,
+        group: Option<&FunctionDefaults>,
+    ) -> Result<(), RegisterError> {
+        let mut registry = self.funcs_mut();
+        let mut pending: HashMap<String, String> = HashMap::new();
+
+        for func in &funcs {
+            if self.strict_ids && func.generated_for.is_none() {
+                let slug = slugify(&func.opts.id);
+                if slug != func.opts.id {
+                    return Err(RegisterError::NonCanonicalId {
+                        id: func.opts.id.clone(),
+                        slug,
+                    });
+                }
+            }
+
+            let slug = func.slug();
+            let existing = registry
+                .get(&slug)
+                .map(|existing| existing.describe())
+                .or_else(|| pending.get(&slug).cloned());
+            if let Some(existing) = existing {
+                return Err(RegisterError::DuplicateId {
+                    slug,
+                    function: func.describe(),
+                    existing,
+                });
             }
+
+            pending.insert(slug, func.describe());
         }
+
+        for func in &mut funcs {
+            func.group_defaults = group.cloned();
+        }
+
+        for func in funcs {
+            registry.insert(func.slug(), Arc::new(func));
+        }
+
+        Ok(())
     }
 
     fn app_serve_origin(&self, headers: &Headers) -> String {
@@ -539,13 +1199,49 @@ impl Handler {
         key.and_then(|key| Signature::new(&key).hash().ok())
     }
 
+    /// Returns the exact JSON body the next sync would send, without sending
+    /// it.
+    ///
+    /// Function URLs use the configured serve origin and path, so set
+    /// [`Handler::serve_origin`] to the public origin when comparing against a
+    /// deployed app. Functions are sorted by ID so the output is stable.
+    pub fn sync_payload_preview(&self) -> Result<Value, Error> {
+        let req = self.sync_payload(
+            &Headers::from(axum::http::HeaderMap::new()),
+            DEFAULT_FRAMEWORK,
+        )?;
+        serde_json::to_value(req)
+            .map_err(|err| basic_error!("error serializing sync payload: {}", err))
+    }
+
+    /// Compares [`Handler::sync_payload_preview`] against a previously
+    /// stored payload, such as the preview saved from the last deploy.
+    pub fn sync_diff(&self, previous: &Value) -> Result<SyncDiff, Error> {
+        Ok(SyncDiff::between(previous, &self.sync_payload_preview()?))
+    }
+
     fn sync_payload(&self, headers: &Headers, framework: &str) -> Result<Request, Error> {
+        self.sync_payload_for(&SyncTarget {
+            serve_origin: self.app_serve_origin(headers),
+            serve_path: self.app_serve_path(),
+            framework: framework.to_string(),
+        })
+    }
+
+    fn sync_payload_for(&self, target: &SyncTarget) -> Result<Request, Error> {
         let app_id = self.inngest.app_id();
-        let functions: Vec<Function> = self
-            .funcs
+        for func in self.funcs().values() {
+            func.check_event_names()
+                .map_err(|err| basic_error!("invalid function config: {}", err))?;
+        }
+
+        let mut functions: Vec<Function> = self
+            .funcs()
             .values()
-            .map(|f| f.function(&self.app_serve_origin(headers), &self.app_serve_path()))
+            .map(|f| f.function(&target.serve_origin, &target.serve_path, &self.defaults))
             .collect();
+        functions.sort_by(|a, b| a.id.cmp(&b.id));
+        self.scope_any_failure_trigger(&mut functions);
 
         for function in &functions {
             function
@@ -555,29 +1251,91 @@ impl Handler {
 
         Ok(Request {
             app_name: app_id.clone(),
-            framework: framework.to_string(),
+            framework: target.framework.clone(),
             functions,
-            url: format!(
-                "{}{}",
-                self.app_serve_origin(headers),
-                self.app_serve_path()
-            ),
+            url: format!("{}{}", target.serve_origin, target.serve_path),
             ..Default::default()
         })
     }
 
+    /// Limits the `on_any_failure` trigger to the functions being synced.
+    fn scope_any_failure_trigger(&self, functions: &mut [Function]) {
+        let funcs = self.funcs();
+        let Some(any_failure_id) = funcs
+            .values()
+            .find(|f| matches!(f.generated_for, Some(Generated::AnyFailure)))
+            .map(|f| f.slug())
+        else {
+            return;
+        };
+
+        // Generated handlers such as `on_failure` are not retried and would
+        // only report their own parent's failure again.
+        let mut ids: Vec<String> = funcs
+            .values()
+            .filter(|f| f.generated_for.is_none())
+            .map(|f| format!("\"{}\"", f.slug()))
+            .collect();
+        ids.sort();
+
+        if let Some(function) = functions.iter_mut().find(|f| f.id == any_failure_id) {
+            function.triggers = vec![Trigger::event(FUNCTION_FAILED_EVENT)
+                .expr(&format!("event.data.function_id in [{}]", ids.join(", ")))];
+        }
+    }
+
     pub async fn sync(
         &self,
-        _headers: &Headers,
+        headers: &Headers,
         query: &SyncQueryParams,
         framework: &str,
     ) -> Result<SyncResponse, String> {
-        let req = self
-            .sync_payload(_headers, framework)
-            .map_err(|err| match err {
+        let target = SyncTarget {
+            serve_origin: self.app_serve_origin(headers),
+            serve_path: self.app_serve_path(),
+            framework: framework.to_string(),
+        };
+        self.sync_to(&target, query)
+            .await
+            .map_err(|err| err.to_string())
+    }
+
+    /// Syncs to `target` and records the outcome in the sync status.
+    async fn sync_to(
+        &self,
+        target: &SyncTarget,
+        query: &SyncQueryParams,
+    ) -> Result<SyncResponse, SyncError> {
+        let _guard = self.sync_lock.lock().await;
+        let result = self.register(target, query).await;
+
+        match &result {
+            Ok(SyncResponse::OutOfBand(res)) => {
+                let mut state = self.sync_state();
+                state.target = Some(target.clone());
+                state.status = SyncStatus::Synced {
+                    at: SystemTime::now(),
+                    modified: res.modified,
+                };
+            }
+            Ok(SyncResponse::InBand(_)) => {}
+            Err(err) => self.record_sync_failure(&err.to_string()),
+        }
+
+        result
+    }
+
+    async fn register(
+        &self,
+        target: &SyncTarget,
+        query: &SyncQueryParams,
+    ) -> Result<SyncResponse, SyncError> {
+        let req = self.sync_payload_for(target).map_err(|err| {
+            SyncError::Local(match err {
                 Error::Dev(DevError::Basic(message)) => message,
                 other => format!("{other:?}"),
-            })?;
+            })
+        })?;
         let sync_url = format!(
             "{}/fn/register",
             self.inngest.inngest_api_origin().trim_end_matches('/')
@@ -600,16 +1358,18 @@ impl Handler {
         let body = match resp.text().await {
             Ok(body) => body,
             Err(err) => {
-                return Err(format!("error reading sync response: {}", err));
+                return Err(SyncError::Network(format!(
+                    "error reading sync response: {}",
+                    err
+                )));
             }
         };
 
         if !status.is_success() {
-            return Err(format!(
-                "error registering: status {} body {}",
-                status.as_u16(),
-                body
-            ));
+            return Err(SyncError::Api {
+                status: status.as_u16(),
+                body,
+            });
         }
 
         match serde_json::from_str::<InngestSyncSuccess>(&body) {
@@ -621,10 +1381,10 @@ impl Handler {
                     modified,
                 })))
             }
-            Err(err) => Err(format!(
+            Err(err) => Err(SyncError::Decode(format!(
                 "error parsing sync response: {} body {}",
                 err, body
-            )),
+            ))),
         }
     }
 
@@ -655,7 +1415,7 @@ impl Handler {
         }
 
         // find the specified function
-        let Some(func) = self.funcs.get(&query.fn_id) else {
+        let Some(func) = self.funcs().get(&query.fn_id).cloned() else {
             return Err(basic_error!(
                 "no function registered as ID: {}",
                 &query.fn_id
@@ -664,7 +1424,8 @@ impl Handler {
 
         let hydrated_body = self.hydrate_run_body(body).await?;
 
-        func.run(query.clone(), hydrated_body).await
+        func.run(query.clone(), hydrated_body, self.extensions.clone())
+            .await
     }
     // run the function
 
@@ -712,21 +1473,18 @@ impl Handler {
     }
 
     async fn fetch_run_resource<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
-        let mut response = self
-            .send_api_get(path, self.signing_key.as_deref())
+        let response = self
+            .inngest
+            .send_api_request(
+                reqwest::Method::GET,
+                path,
+                None,
+                self.signing_key.as_deref(),
+                self.signing_key_fallback.as_deref(),
+            )
             .await
             .map_err(|err| basic_error!("{}", err))?;
 
-        if response.status() == reqwest::StatusCode::UNAUTHORIZED
-            && self.signing_key.is_some()
-            && self.signing_key_fallback.is_some()
-        {
-            response = self
-                .send_api_get(path, self.signing_key_fallback.as_deref())
-                .await
-                .map_err(|err| basic_error!("{}", err))?;
-        }
-
         let status = response.status();
         if !status.is_success() {
             return Err(basic_error!(
@@ -742,46 +1500,16 @@ impl Handler {
             .map_err(|err| basic_error!("error decoding run payload from {}: {}", path, err))
     }
 
-    async fn send_api_get(
-        &self,
-        path: &str,
-        auth_key: Option<&str>,
-    ) -> Result<reqwest::Response, String> {
-        let url = format!(
-            "{}{}",
-            self.inngest.inngest_api_origin().trim_end_matches('/'),
-            path
-        );
-        let mut request = reqwest::Client::new()
-            .get(url)
-            .header(header::INNGEST_SDK, version::sdk())
-            .header(header::INNGEST_REQ_VERSION, EXECUTION_VERSION);
-
-        if let Some(env) = self.inngest.env.clone() {
-            request = request.header(header::INNGEST_ENV, env);
-        }
-
-        if let Some(key) = auth_key {
-            let hashed = Signature::new(key)
-                .hash()
-                .map_err(|_| "error hashing signing key".to_string())?;
-            request = request.header("authorization", format!("Bearer {}", hashed));
-        }
-
-        request.send().await.map_err(|err| {
-            println!("ERROR: {:?}", err);
-            format!("error fetching run payload from {}", path)
-        })
-    }
-
     async fn send_sync_request(
         &self,
         sync_url: &str,
         req: &Request,
         query: &SyncQueryParams,
         auth_key: Option<&str>,
-    ) -> Result<reqwest::Response, String> {
-        let mut sync_req = reqwest::Client::new()
+    ) -> Result<reqwest::Response, SyncError> {
+        let mut sync_req = self
+            .inngest
+            .http()
             .post(sync_url)
             .json(req)
             .header(header::INNGEST_SDK, version::sdk())
@@ -794,7 +1522,7 @@ impl Handler {
         if let Some(key) = auth_key {
             let hashed = Signature::new(key)
                 .hash()
-                .map_err(|_| "error hashing signing key".to_string())?;
+                .map_err(|_| SyncError::Local("error hashing signing key".to_string()))?;
             sync_req = sync_req.header("authorization", format!("Bearer {}", hashed));
         }
 
@@ -804,11 +1532,50 @@ impl Handler {
 
         sync_req.send().await.map_err(|err| {
             println!("ERROR: {:?}", err);
-            "error registering".to_string()
+            SyncError::Network("error registering".to_string())
         })
     }
 }
 
+/// Why a sync failed, used to decide whether retrying can help.
+#[derive(Debug)]
+enum SyncError {
+    /// The payload or request could not be built, for example because a
+    /// function's config is invalid.
+    Local(String),
+    /// The request could not be sent or its response could not be read.
+    Network(String),
+    /// Inngest responded with a non-success status.
+    Api { status: u16, body: String },
+    /// Inngest accepted the sync but its response could not be parsed.
+    Decode(String),
+}
+
+impl SyncError {
+    /// Network failures and `5xx` responses may succeed on a later attempt;
+    /// rejected keys, invalid config and other `4xx` responses will not.
+    fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network(_) => true,
+            Self::Api { status, .. } => *status >= 500,
+            Self::Local(_) | Self::Decode(_) => false,
+        }
+    }
+}
+
+impl std::fmt::Display for SyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local(message) | Self::Network(message) | Self::Decode(message) => {
+                write!(f, "{}", message)
+            }
+            Self::Api { status, body } => {
+                write!(f, "error registering: status {} body {}", status, body)
+            }
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct RunRequestBody<T: 'static> {
     ctx: RunRequestCtx,
@@ -820,6 +1587,53 @@ struct RunRequestBody<T: 'static> {
     // version: i32,
 }
 
+impl<T: InngestEvent> RunRequestBody<T> {
+    /// Parses a run request. With `event_names`, set for functions using
+    /// [`ServableFn::dispatch_by_event_name`], each event is first checked
+    /// against the accepted names and then wrapped as `{ "name", "data" }`
+    /// so it deserializes into the matching variant of the payload enum.
+    fn parse(body: &Value, event_names: Option<&[&str]>) -> Result<Self, String> {
+        let Some(event_names) = event_names else {
+            return serde_json::from_value::<Self>(body.clone()).map_err(|err| err.to_string());
+        };
+
+        let mut tagged = body.clone();
+        let mut events: Vec<&mut Value> = Vec::new();
+        if let Value::Object(obj) = &mut tagged {
+            for (key, value) in obj.iter_mut() {
+                match (key.as_str(), value) {
+                    ("event", event) => events.push(event),
+                    ("events", Value::Array(batch)) => events.extend(batch.iter_mut()),
+                    _ => {}
+                }
+            }
+        }
+
+        for event in events {
+            let name = event
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            if !event_names.contains(&name.as_str()) {
+                return Err(format!(
+                    "event {} is not mapped to any variant of {}",
+                    name,
+                    std::any::type_name::<T>()
+                ));
+            }
+
+            let data = event
+                .get_mut("data")
+                .map(Value::take)
+                .unwrap_or(Value::Null);
+            event["data"] = json!({ "name": name, "data": data });
+        }
+
+        serde_json::from_value::<Self>(tagged).map_err(|err| err.to_string())
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct RunRequestCtx {
     attempt: u8,
@@ -962,12 +1776,15 @@ fn run_request_uses_api(body: &Value) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::event::EventEnum;
     use crate::function::{
-        FunctionBatchEvents, FunctionCancel, FunctionConcurrency, FunctionConcurrencyOption,
-        FunctionConcurrencyScope, FunctionDebounce, FunctionFailureEvent, FunctionPriority,
-        FunctionRateLimit, FunctionSingleton, FunctionSingletonMode, FunctionThrottle,
-        FunctionTimeouts, ServableFn,
+        FunctionBatchEvents, FunctionCancel, FunctionCancelledEvent, FunctionConcurrency,
+        FunctionConcurrencyOption, FunctionConcurrencyScope, FunctionDebounce, FunctionDefaults,
+        FunctionFailureEvent, FunctionFinishedEvent, FunctionPriority, FunctionRateLimit,
+        FunctionSingleton, FunctionSingletonMode, FunctionSuccessEvent, FunctionThrottle,
+        FunctionTimeouts, IntoTriggers, ServableFn,
     };
+    use crate::utils::test_server::spawn_test_server;
     use axum::{
         extract::State,
         http::{HeaderMap, StatusCode, Uri},
@@ -980,8 +1797,10 @@ mod tests {
     use sha2::Sha256;
     use std::{
         collections::{HashSet, VecDeque},
-        net::TcpListener,
-        sync::Arc,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
         time::Duration,
     };
     use tokio::sync::Mutex;
@@ -996,6 +1815,21 @@ mod tests {
         count: u32,
     }
 
+    #[derive(Debug, Deserialize, Serialize)]
+    #[serde(tag = "name", content = "data")]
+    enum TestEvents {
+        #[serde(rename = "test/first")]
+        First(FirstEvent),
+        #[serde(rename = "test/second")]
+        Second(SecondEvent),
+    }
+
+    impl EventEnum for TestEvents {
+        fn event_names() -> &'static [&'static str] {
+            &["test/first", "test/second"]
+        }
+    }
+
     #[derive(Debug, Deserialize, Serialize)]
     struct StepTestError {
         message: String,
@@ -1020,6 +1854,8 @@ mod tests {
     const FALLBACK_SIGNING_KEY: &str =
         "signkey-test-1111111111111111111111111111111111111111111111111111111111111111";
 
+    const TEST_TRANSPORT_HEADER: &str = "x-test-transport";
+
     #[derive(Clone, Debug, Default, PartialEq, Eq)]
     struct SyncRequestRecord {
         authorization: Option<String>,
@@ -1029,12 +1865,16 @@ mod tests {
         query: Option<String>,
         req_version: Option<String>,
         sdk: Option<String>,
+        transport: Option<String>,
     }
 
     #[derive(Clone)]
     struct SyncServerState {
         records: Arc<Mutex<Vec<SyncRequestRecord>>>,
         responses: Arc<Mutex<VecDeque<(StatusCode, String)>>>,
+        delay: Duration,
+        in_flight: Arc<AtomicUsize>,
+        max_in_flight: Arc<AtomicUsize>,
     }
 
     #[tokio::test]
@@ -1060,7 +1900,9 @@ mod tests {
         );
         let second_fn_id = second_fn.slug();
 
-        handler.register_fns(vec![first_fn.into(), second_fn.into()]);
+        handler
+            .register_fns(vec![first_fn.into(), second_fn.into()])
+            .expect("functions should register");
 
         let headers = Headers::from(HeaderMap::new());
 
@@ -1128,7 +1970,9 @@ mod tests {
             },
         );
         let first_fn_id = first_fn.slug();
-        handler.register_fn(first_fn);
+        handler
+            .register_fn(first_fn)
+            .expect("function should register");
 
         let second_fn: ServableFn<SecondEvent, Error> = client.create_function(
             FunctionOpts::new("second"),
@@ -1138,7 +1982,9 @@ mod tests {
             },
         );
         let second_fn_id = second_fn.slug();
-        handler.register_fns(vec![second_fn.into()]);
+        handler
+            .register_fns(vec![second_fn.into()])
+            .expect("functions should register");
 
         let headers = Headers::from(HeaderMap::new());
 
@@ -1166,144 +2012,666 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn handler_returns_error_for_unknown_function_id() {
+    async fn handler_dispatches_enum_payloads_by_event_name() {
         let client = Inngest::new("test-app").dev("1");
-        let handler = Handler::new(&client);
+        let mut handler = Handler::new(&client);
+        let func: ServableFn<TestEvents, Error> = client
+            .create_function(
+                FunctionOpts::new("multi-event"),
+                [Trigger::event("test/first"), Trigger::event("test/second")],
+                |input: Input<TestEvents>, _step| async move {
+                    match input.event.data {
+                        TestEvents::First(first) => Ok(json!({ "message": first.message })),
+                        TestEvents::Second(second) => Ok(json!({ "count": second.count })),
+                    }
+                },
+            )
+            .dispatch_by_event_name();
+        let fn_id = func.slug();
+        handler.register_fn(func).expect("function should register");
+
         let headers = Headers::from(HeaderMap::new());
-        let body = event_body("test/first", json!({ "message": "hello" }));
+        for (body, expected) in [
+            (
+                event_body("test/first", json!({ "message": "hello" })),
+                json!({ "message": "hello" }),
+            ),
+            (
+                event_body("test/second", json!({ "count": 42 })),
+                json!({ "count": 42 }),
+            ),
+        ] {
+            let response = handler
+                .run(
+                    &headers,
+                    &run_query(fn_id.clone()),
+                    &body.to_string(),
+                    &body,
+                )
+                .await
+                .expect("enum payload should dispatch by event name");
 
+            assert_eq!(response.body, expected);
+        }
+
+        let func_id = fn_id.clone();
+        let body = event_body("test/third", json!({}));
         let error = match handler
-            .run(
-                &headers,
-                &run_query("test-app-missing".to_string()),
-                &body.to_string(),
-                &body,
-            )
+            .run(&headers, &run_query(fn_id), &body.to_string(), &body)
             .await
         {
-            Ok(_) => panic!("missing function id should fail"),
-            Err(error) => error,
+            Ok(_) => panic!("expected unmapped event to fail"),
+            Err(err) => err,
         };
 
+        assert_basic_error(
+            error,
+            "event test/third is not mapped to any variant of inngest::handler::tests::TestEvents",
+        );
+
+        let body = event_body("test/second", json!({ "count": "many" }));
+        let error = match handler
+            .run(&headers, &run_query(func_id), &body.to_string(), &body)
+            .await
+        {
+            Ok(_) => panic!("expected an invalid payload to fail"),
+            Err(err) => err,
+        };
         match error {
             Error::Dev(crate::result::DevError::Basic(message)) => {
-                assert!(message.contains("no function registered as ID"));
+                assert!(message.contains("invalid type"), "{message}");
+                assert!(!message.contains("not mapped"), "{message}");
             }
             other => panic!("unexpected error: {other:?}"),
         }
     }
 
+    #[derive(Debug, Serialize)]
+    struct TypedOutput {
+        message: String,
+        length: usize,
+    }
+
     #[tokio::test]
-    async fn handler_returns_error_for_mismatched_event_payload() {
+    async fn handler_serializes_typed_function_output() {
         let client = Inngest::new("test-app").dev("1");
         let mut handler = Handler::new(&client);
-
-        let first_fn: ServableFn<FirstEvent, Error> = client.create_function(
-            FunctionOpts::new("first"),
+        let func: ServableFn<FirstEvent, Error, TypedOutput> = client.create_function(
+            FunctionOpts::new("typed"),
             Trigger::event("test/first"),
             |input: Input<FirstEvent>, _step| async move {
-                Ok(json!({ "message": input.event.data.message }))
+                Ok(TypedOutput {
+                    length: input.event.data.message.len(),
+                    message: input.event.data.message,
+                })
             },
         );
-        let first_fn_id = first_fn.slug();
-        handler.register_fn(first_fn);
-
-        let headers = Headers::from(HeaderMap::new());
-        let body = event_body("test/first", json!({ "count": 42 }));
+        let fn_id = func.slug();
+        handler.register_fn(func).expect("function should register");
 
-        let error = match handler
-            .run(&headers, &run_query(first_fn_id), &body.to_string(), &body)
+        let body = event_body("test/first", json!({ "message": "hello" }));
+        let response = handler
+            .run(
+                &Headers::from(HeaderMap::new()),
+                &run_query(fn_id),
+                &body.to_string(),
+                &body,
+            )
             .await
-        {
-            Ok(_) => panic!("mismatched payload should fail"),
-            Err(error) => error,
-        };
+            .expect("typed function should run");
 
-        match error {
-            Error::Dev(crate::result::DevError::Basic(message)) => {
-                assert!(message.contains("error parsing run request"));
-            }
-            other => panic!("unexpected error: {other:?}"),
-        }
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, json!({ "message": "hello", "length": 5 }));
     }
 
-    #[test]
-    fn sync_payload_includes_all_registered_functions() {
-        let client = Inngest::new("test-app");
-        let mut handler = Handler::new(&client);
+    struct Greeter {
+        greeting: &'static str,
+    }
 
-        let first_fn: ServableFn<FirstEvent, Error> = client.create_function(
-            FunctionOpts::new("first"),
+    async fn greet(input: Input<FirstEvent>, _step: StepTool) -> Result<Value, Error> {
+        let greeter = input
+            .state::<Greeter>()
+            .ok_or_else(|| basic_error!("greeter should be registered"))?;
+        Ok(json!(format!(
+            "{} {}",
+            greeter.greeting, input.event.data.message
+        )))
+    }
+
+    #[tokio::test]
+    async fn handler_passes_shared_state_to_plain_fn_functions() {
+        let client = Inngest::new("test-app").dev("1");
+        let mut handler = Handler::new(&client).state(Greeter { greeting: "hello" });
+        let func = client.create_function(
+            FunctionOpts::new("greet"),
             Trigger::event("test/first"),
-            |input: Input<FirstEvent>, _step| async move {
-                Ok(json!({ "message": input.event.data.message }))
-            },
-        );
-        let second_fn: ServableFn<SecondEvent, Error> = client.create_function(
-            FunctionOpts::new("second"),
-            Trigger::event("test/second"),
-            |input: Input<SecondEvent>, _step| async move {
-                Ok(json!({ "count": input.event.data.count }))
-            },
+            greet,
         );
+        let fn_id = func.slug();
+        handler.register_fn(func).expect("function should register");
+
+        let body = event_body("test/first", json!({ "message": "world" }));
+        let response = handler
+            .run(
+                &Headers::from(HeaderMap::new()),
+                &run_query(fn_id),
+                &body.to_string(),
+                &body,
+            )
+            .await
+            .expect("function should run");
+
+        assert_eq!(response.body, json!("hello world"));
+    }
+
+    #[derive(Serialize)]
+    struct Greeting {
+        text: String,
+    }
+
+    struct GreetFn {
+        greeter: Greeter,
+    }
+
+    impl InngestFunction for GreetFn {
+        type Event = FirstEvent;
+        type Output = Greeting;
+        type Error = Error;
+
+        fn opts(&self) -> FunctionOpts {
+            FunctionOpts::new("struct-greet").retries(1)
+        }
+
+        fn trigger(&self) -> impl IntoTriggers {
+            Trigger::event("test/first")
+        }
+
+        async fn run(&self, input: Input<FirstEvent>, _step: StepTool) -> Result<Greeting, Error> {
+            Ok(Greeting {
+                text: format!("{} {}", self.greeter.greeting, input.event.data.message),
+            })
+        }
+    }
 
-        handler.register_fns(vec![first_fn.into(), second_fn.into()]);
+    #[tokio::test]
+    async fn handler_registers_and_runs_trait_functions() {
+        let client = Inngest::new("test-app").dev("1");
+        let mut handler = Handler::new(&client);
+        handler
+            .register_fn(GreetFn {
+                greeter: Greeter { greeting: "hi" },
+            })
+            .expect("function should register");
 
+        let headers = Headers::from(HeaderMap::new());
         let payload = handler
-            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .sync_payload(&headers, "axum")
             .expect("sync payload should serialize");
-        let function_ids: HashSet<String> = payload
+        let synced = payload
             .functions
-            .into_iter()
-            .map(|function| function.id)
-            .collect();
+            .iter()
+            .find(|function| function.id == "test-app-struct-greet")
+            .expect("trait function should be synced");
+        assert_eq!(synced.steps["step"].retries.attempts, 1);
 
-        assert_eq!(payload.app_name, "test-app");
-        assert_eq!(payload.framework, "axum");
-        assert_eq!(payload.v, "0.1");
-        assert_eq!(payload.sdk, crate::version::sdk());
-        assert_eq!(payload.url, "http://127.0.0.1:3000/api/inngest");
-        assert_eq!(function_ids.len(), 2);
-        assert!(function_ids.contains("test-app-first"));
-        assert!(function_ids.contains("test-app-second"));
+        let body = event_body("test/first", json!({ "message": "there" }));
+        let response = handler
+            .run(
+                &headers,
+                &run_query("test-app-struct-greet".to_string()),
+                &body.to_string(),
+                &body,
+            )
+            .await
+            .expect("trait function should run");
+
+        assert_eq!(response.body, json!({ "text": "hi there" }));
     }
 
-    #[test]
-    fn sync_payload_uses_spec_step_runtime_url_shape() {
-        let client = Inngest::new("test-app");
+    #[tokio::test]
+    async fn on_cancel_handler_is_synced_and_receives_cancelled_run() {
+        let client = Inngest::new("test-app").dev("1");
         let mut handler = Handler::new(&client);
-
-        let first_fn: ServableFn<FirstEvent, Error> = client.create_function(
-            FunctionOpts::new("first"),
-            Trigger::event("test/first"),
-            |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
-        );
-
-        handler.register_fn(first_fn);
+        let func: ServableFn<FirstEvent, Error> = client
+            .create_function(
+                FunctionOpts::new("cancellable").cancel(
+                    FunctionCancel::new("test/stop").if_exp("event.data.id == async.data.id"),
+                ),
+                Trigger::event("test/first"),
+                |_input: Input<FirstEvent>, _step| async move { Ok(json!({})) },
+            )
+            .on_cancel(
+                |input: Input<FunctionCancelledEvent<FirstEvent>>, _step| async move {
+                    Ok(json!({
+                        "run_id": input.event.data.run_id,
+                        "message": input.event.data.event.data.message,
+                    }))
+                },
+            );
+        handler.register_fn(func).expect("function should register");
 
         let payload = handler
             .sync_payload(&Headers::from(HeaderMap::new()), "axum")
             .expect("sync payload should serialize");
-        let function = payload
+        let cancel_fn = payload
             .functions
-            .into_iter()
-            .next()
-            .expect("registered function should be present");
-        let step = function
-            .steps
-            .get("step")
-            .expect("default step should be present");
+            .iter()
+            .find(|function| function.id == "test-app-cancellable-cancel")
+            .expect("cancel handler should be synced");
+        assert_eq!(cancel_fn.name, "test-app-cancellable (cancel)");
+        assert_eq!(
+            cancel_fn.triggers,
+            vec![Trigger::event("inngest/function.cancelled")
+                .expr("event.data.function_id == \"test-app-cancellable\"")]
+        );
+        assert!(cancel_fn.cancel.is_empty());
+        assert_eq!(cancel_fn.steps["step"].retries.attempts, 0);
+
+        let body = event_body(
+            "inngest/function.cancelled",
+            json!({
+                "function_id": "test-app-cancellable",
+                "run_id": "run-1",
+                "event": { "name": "test/first", "data": { "message": "half done" } },
+            }),
+        );
+        let response = handler
+            .run(
+                &Headers::from(HeaderMap::new()),
+                &run_query("test-app-cancellable-cancel".to_string()),
+                &body.to_string(),
+                &body,
+            )
+            .await
+            .expect("cancel handler should run");
 
         assert_eq!(
-            step.runtime.url,
-            "http://127.0.0.1:3000/api/inngest?fnId=test-app-first&stepId=step"
+            response.body,
+            json!({ "run_id": "run-1", "message": "half done" })
         );
-        assert!(!payload.url.contains("deployId="));
     }
 
-    #[test]
-    fn sync_payload_serializes_function_config_metadata() {
-        let client = Inngest::new("test-app");
+    #[tokio::test]
+    async fn on_any_failure_covers_every_function_in_the_app() {
+        let client = Inngest::new("test-app").dev("1");
+        let mut handler = Handler::new(&client);
+        handler
+            .on_any_failure(
+                |input: Input<FunctionFailureEvent<Value>>, _step| async move {
+                    Ok::<_, Error>(json!({
+                        "function_id": input.event.data.function_id,
+                        "original": input.event.data.event.data,
+                    }))
+                },
+            )
+            .expect("failure handler should register");
+        handler
+            .register_fns(vec![
+                noop_fn(&client, "first").into(),
+                noop_fn(&client, "second")
+                    .on_failure(
+                        |_input: Input<FunctionFailureEvent<FirstEvent>>, _step| async move {
+                            Ok(json!({}))
+                        },
+                    )
+                    .into(),
+            ])
+            .expect("functions should register");
+
+        let payload = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect("sync payload should serialize");
+        assert!(payload
+            .functions
+            .iter()
+            .any(|function| function.id == "test-app-second-failure"));
+        let any_failure = payload
+            .functions
+            .iter()
+            .find(|function| function.id == "test-app-on-any-failure")
+            .expect("app failure handler should be synced");
+        assert_eq!(any_failure.name, "test-app (any failure)");
+        assert_eq!(
+            any_failure.triggers,
+            vec![Trigger::event("inngest/function.failed")
+                .expr("event.data.function_id in [\"test-app-first\", \"test-app-second\"]")]
+        );
+        assert_eq!(any_failure.steps["step"].retries.attempts, 0);
+
+        let body = event_body(
+            "inngest/function.failed",
+            json!({
+                "function_id": "test-app-second",
+                "run_id": "run-1",
+                "error": { "name": "Error", "message": "boom" },
+                "event": { "name": "test/first", "data": { "message": "hi" } },
+            }),
+        );
+        let response = handler
+            .run(
+                &Headers::from(HeaderMap::new()),
+                &run_query("test-app-on-any-failure".to_string()),
+                &body.to_string(),
+                &body,
+            )
+            .await
+            .expect("app failure handler should run");
+        assert_eq!(
+            response.body,
+            json!({ "function_id": "test-app-second", "original": { "message": "hi" } })
+        );
+
+        let err = handler
+            .on_any_failure(
+                |_input: Input<FunctionFailureEvent<Value>>, _step| async move {
+                    Ok::<_, Error>(json!({}))
+                },
+            )
+            .expect_err("a second app failure handler should be rejected");
+        assert_eq!(
+            err.to_string(),
+            "the app's on_any_failure handler collides with the app's on_any_failure handler: both resolve to ID test-app-on-any-failure"
+        );
+    }
+
+    #[tokio::test]
+    async fn lifecycle_handlers_receive_typed_finished_events() {
+        let client = Inngest::new("test-app").dev("1");
+        let mut handler = Handler::new(&client);
+        let func: ServableFn<FirstEvent, Error, SecondEvent> = client
+            .create_function(
+                FunctionOpts::new("counted"),
+                Trigger::event("test/first"),
+                |_input: Input<FirstEvent>, _step| async move { Ok(SecondEvent { count: 7 }) },
+            )
+            .on_finish(
+                |input: Input<FunctionFinishedEvent<FirstEvent, SecondEvent>>, _step| async move {
+                    match input.event.data.outcome() {
+                        Ok(result) => Ok(json!({ "count": result.map(|r| r.count) })),
+                        Err(error) => Ok(json!({ "error": error.message })),
+                    }
+                },
+            )
+            .on_success(
+                |input: Input<FunctionSuccessEvent<FirstEvent, SecondEvent>>, _step| async move {
+                    Ok::<_, Error>(json!({ "count": input.event.data.result.count }))
+                },
+            );
+        handler.register_fn(func).expect("function should register");
+
+        let payload = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect("sync payload should serialize");
+        let mut hooks: Vec<_> = payload
+            .functions
+            .iter()
+            .filter(|function| function.id != "test-app-counted")
+            .map(|function| {
+                (
+                    function.id.clone(),
+                    function.name.clone(),
+                    serde_json::to_value(&function.triggers).expect("triggers should serialize"),
+                )
+            })
+            .collect();
+        hooks.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            hooks,
+            vec![
+                (
+                    "test-app-counted-finish".to_string(),
+                    "test-app-counted (finish)".to_string(),
+                    json!([{
+                        "event": "inngest/function.finished",
+                        "expression": "event.data.function_id == \"test-app-counted\"",
+                    }]),
+                ),
+                (
+                    "test-app-counted-success".to_string(),
+                    "test-app-counted (success)".to_string(),
+                    json!([{
+                        "event": "inngest/function.finished",
+                        "expression": "event.data.function_id == \"test-app-counted\" && !has(event.data.error)",
+                    }]),
+                ),
+            ]
+        );
+
+        let headers = Headers::from(HeaderMap::new());
+        let succeeded = event_body(
+            "inngest/function.finished",
+            json!({
+                "function_id": "test-app-counted",
+                "run_id": "run-1",
+                "event": { "name": "test/first", "data": { "message": "hi" } },
+                "result": { "count": 7 },
+            }),
+        );
+        let failed = event_body(
+            "inngest/function.finished",
+            json!({
+                "function_id": "test-app-counted",
+                "run_id": "run-2",
+                "event": { "name": "test/first", "data": { "message": "hi" } },
+                "error": { "name": "Error", "message": "boom" },
+            }),
+        );
+
+        for (fn_id, body, expected) in [
+            ("test-app-counted-finish", &succeeded, json!({ "count": 7 })),
+            (
+                "test-app-counted-finish",
+                &failed,
+                json!({ "error": "boom" }),
+            ),
+            (
+                "test-app-counted-success",
+                &succeeded,
+                json!({ "count": 7 }),
+            ),
+        ] {
+            let response = handler
+                .run(
+                    &headers,
+                    &run_query(fn_id.to_string()),
+                    &body.to_string(),
+                    body,
+                )
+                .await
+                .expect("lifecycle handler should run");
+            assert_eq!(response.body, expected, "{fn_id}");
+        }
+    }
+
+    #[tokio::test]
+    async fn handler_returns_error_for_unknown_function_id() {
+        let client = Inngest::new("test-app").dev("1");
+        let handler = Handler::new(&client);
+        let headers = Headers::from(HeaderMap::new());
+        let body = event_body("test/first", json!({ "message": "hello" }));
+
+        let error = match handler
+            .run(
+                &headers,
+                &run_query("test-app-missing".to_string()),
+                &body.to_string(),
+                &body,
+            )
+            .await
+        {
+            Ok(_) => panic!("missing function id should fail"),
+            Err(error) => error,
+        };
+
+        match error {
+            Error::Dev(crate::result::DevError::Basic(message)) => {
+                assert!(message.contains("no function registered as ID"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn handler_returns_error_for_mismatched_event_payload() {
+        let client = Inngest::new("test-app").dev("1");
+        let mut handler = Handler::new(&client);
+
+        let first_fn: ServableFn<FirstEvent, Error> = client.create_function(
+            FunctionOpts::new("first"),
+            Trigger::event("test/first"),
+            |input: Input<FirstEvent>, _step| async move {
+                Ok(json!({ "message": input.event.data.message }))
+            },
+        );
+        let first_fn_id = first_fn.slug();
+        handler
+            .register_fn(first_fn)
+            .expect("function should register");
+
+        let headers = Headers::from(HeaderMap::new());
+        let body = event_body("test/first", json!({ "count": 42 }));
+
+        let error = match handler
+            .run(&headers, &run_query(first_fn_id), &body.to_string(), &body)
+            .await
+        {
+            Ok(_) => panic!("mismatched payload should fail"),
+            Err(error) => error,
+        };
+
+        match error {
+            Error::Dev(crate::result::DevError::Basic(message)) => {
+                assert!(message.contains("error parsing run request"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn sync_payload_includes_all_registered_functions() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+
+        let first_fn: ServableFn<FirstEvent, Error> = client.create_function(
+            FunctionOpts::new("first"),
+            Trigger::event("test/first"),
+            |input: Input<FirstEvent>, _step| async move {
+                Ok(json!({ "message": input.event.data.message }))
+            },
+        );
+        let second_fn: ServableFn<SecondEvent, Error> = client.create_function(
+            FunctionOpts::new("second"),
+            Trigger::event("test/second"),
+            |input: Input<SecondEvent>, _step| async move {
+                Ok(json!({ "count": input.event.data.count }))
+            },
+        );
+
+        handler
+            .register_fns(vec![first_fn.into(), second_fn.into()])
+            .expect("functions should register");
+
+        let payload = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect("sync payload should serialize");
+        let function_ids: HashSet<String> = payload
+            .functions
+            .into_iter()
+            .map(|function| function.id)
+            .collect();
+
+        assert_eq!(payload.app_name, "test-app");
+        assert_eq!(payload.framework, "axum");
+        assert_eq!(payload.v, "0.1");
+        assert_eq!(payload.sdk, crate::version::sdk());
+        assert_eq!(payload.url, "http://127.0.0.1:3000/api/inngest");
+        assert_eq!(function_ids.len(), 2);
+        assert!(function_ids.contains("test-app-first"));
+        assert!(function_ids.contains("test-app-second"));
+    }
+
+    #[test]
+    fn sync_payload_preview_matches_sync_body_and_diffs_against_stored_payload() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client).serve_origin("https://app.example.com");
+        handler
+            .register_fns(vec![
+                noop_fn(&client, "second").into(),
+                noop_fn(&client, "first").into(),
+            ])
+            .expect("functions should register");
+
+        let preview = handler
+            .sync_payload_preview()
+            .expect("preview should serialize");
+        let expected = serde_json::to_value(
+            handler
+                .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+                .expect("sync payload should build"),
+        )
+        .expect("sync payload should serialize");
+        assert_eq!(preview, expected);
+        assert_eq!(preview["url"], json!("https://app.example.com/api/inngest"));
+        assert_eq!(preview["functions"][0]["id"], json!("test-app-first"));
+        assert_eq!(preview["functions"][1]["id"], json!("test-app-second"));
+        assert!(handler
+            .sync_diff(&preview)
+            .expect("diff should build")
+            .is_empty());
+
+        let mut stored = preview.clone();
+        stored["functions"][0]["triggers"] = json!([{ "event": "test/old" }]);
+        stored["functions"]
+            .as_array_mut()
+            .expect("functions should be an array")
+            .remove(1);
+        let diff = handler.sync_diff(&stored).expect("diff should build");
+
+        assert_eq!(diff.added, vec!["test-app-second".to_string()]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].id, "test-app-first");
+        assert_eq!(diff.changed[0].fields[0].field, "triggers");
+    }
+
+    #[test]
+    fn sync_payload_uses_spec_step_runtime_url_shape() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+
+        let first_fn: ServableFn<FirstEvent, Error> = client.create_function(
+            FunctionOpts::new("first"),
+            Trigger::event("test/first"),
+            |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
+        );
+
+        handler
+            .register_fn(first_fn)
+            .expect("function should register");
+
+        let payload = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect("sync payload should serialize");
+        let function = payload
+            .functions
+            .into_iter()
+            .next()
+            .expect("registered function should be present");
+        let step = function
+            .steps
+            .get("step")
+            .expect("default step should be present");
+
+        assert_eq!(
+            step.runtime.url,
+            "http://127.0.0.1:3000/api/inngest?fnId=test-app-first&stepId=step"
+        );
+        assert!(!payload.url.contains("deployId="));
+    }
+
+    #[test]
+    fn sync_payload_serializes_function_config_metadata() {
+        let client = Inngest::new("test-app");
         let mut handler = Handler::new(&client);
 
         let func: ServableFn<FirstEvent, Error> = client.create_function(
@@ -1313,10 +2681,6 @@ mod tests {
                         .if_exp("event.data.id == async.data.id")
                         .timeout(Duration::from_secs(30)),
                 )
-                .batch_events(
-                    FunctionBatchEvents::new(10, Duration::from_secs(45))
-                        .key("event.data.account_id"),
-                )
                 .rate_limit(
                     FunctionRateLimit::new(5, Duration::from_secs(60)).key("event.data.user_id"),
                 )
@@ -1349,7 +2713,7 @@ mod tests {
             |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
         );
 
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let payload = handler
             .sync_payload(&Headers::from(HeaderMap::new()), "axum")
@@ -1383,11 +2747,6 @@ mod tests {
                     "if": "event.data.id == async.data.id",
                     "timeout": "30s"
                 }],
-                "batchEvents": {
-                    "maxSize": 10,
-                    "timeout": "45s",
-                    "key": "event.data.account_id"
-                },
                 "rateLimit": {
                     "key": "event.data.user_id",
                     "limit": 5,
@@ -1425,7 +2784,7 @@ mod tests {
     }
 
     #[test]
-    fn sync_payload_omits_rate_limit_when_idempotency_is_set() {
+    fn sync_payload_rejects_idempotency_combined_with_rate_limit() {
         let client = Inngest::new("test-app");
         let mut handler = Handler::new(&client);
 
@@ -1437,93 +2796,563 @@ mod tests {
             |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
         );
 
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
+
+        let error = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect_err("idempotency and rate limiting should conflict");
+
+        assert_basic_error(
+            error,
+            "function test-app-first idempotency cannot be combined with rateLimit",
+        );
+    }
+
+    #[test]
+    fn sync_payload_rejects_flow_control_outside_spec_ranges() {
+        let cases: Vec<(FunctionOpts, &str)> = vec![
+            (
+                FunctionOpts::new("ranges").retries(21),
+                "function test-app-ranges retries must be between 0 and 20, got 21",
+            ),
+            (
+                FunctionOpts::new("ranges")
+                    .rate_limit(FunctionRateLimit::new(1, Duration::from_millis(500))),
+                "function test-app-ranges rateLimit.period must be between 1s and 1d, got 500ms",
+            ),
+            (
+                FunctionOpts::new("ranges").rate_limit(FunctionRateLimit::new(0, "1m")),
+                "function test-app-ranges rateLimit.limit must be at least 1",
+            ),
+            (
+                FunctionOpts::new("ranges").throttle(FunctionThrottle::new(0, "1m")),
+                "function test-app-ranges throttle.limit must be at least 1",
+            ),
+            (
+                FunctionOpts::new("ranges").throttle(FunctionThrottle::new(1, "1m").burst(0)),
+                "function test-app-ranges throttle.burst must be at least 1",
+            ),
+            (
+                FunctionOpts::new("ranges").debounce(FunctionDebounce::new("8d")),
+                "function test-app-ranges debounce.period must be between 1s and 7d, got 8d",
+            ),
+            (
+                FunctionOpts::new("ranges")
+                    .debounce(FunctionDebounce::new("1m").timeout(Duration::from_secs(8 * 86_400))),
+                "function test-app-ranges debounce.timeout must be between 1s and 7d, got 8d",
+            ),
+            (
+                FunctionOpts::new("ranges").priority(FunctionPriority::new()),
+                "function test-app-ranges priority.run is required",
+            ),
+            (
+                FunctionOpts::new("ranges")
+                    .timeouts(FunctionTimeouts::new().finish(Duration::from_millis(10))),
+                "function test-app-ranges timeouts.finish must be at least 1s, got 10ms",
+            ),
+            (
+                FunctionOpts::new("ranges").cancel(FunctionCancel::new(" ")),
+                "function test-app-ranges cancel.event cannot be empty",
+            ),
+            (
+                FunctionOpts::new("ranges")
+                    .singleton(FunctionSingleton::new(FunctionSingletonMode::Skip).key(" ")),
+                "function test-app-ranges singleton.key: expression cannot be empty",
+            ),
+            (
+                FunctionOpts::new("ranges")
+                    .singleton(FunctionSingleton::new(FunctionSingletonMode::Cancel))
+                    .batch_events(FunctionBatchEvents::new(10, "5s")),
+                "function test-app-ranges singleton cannot be combined with batchEvents",
+            ),
+        ];
+
+        for (opts, expected) in cases {
+            let client = Inngest::new("test-app");
+            let mut handler = Handler::new(&client);
+            let func: ServableFn<Value, Error> = client.create_function(
+                opts,
+                Trigger::event("test/first"),
+                |_input: Input<Value>, _step| async move { Ok(json!({ "ok": true })) },
+            );
+
+            handler.register_fn(func).expect("function should register");
+
+            let error = handler
+                .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+                .expect_err("out of range config should be rejected");
+
+            assert_basic_error(error, expected);
+        }
+    }
+
+    fn noop_fn(client: &Inngest, id: &str) -> ServableFn<FirstEvent, Error> {
+        client.create_function(
+            FunctionOpts::new(id),
+            Trigger::event("test/first"),
+            |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
+        )
+    }
+
+    #[test]
+    fn register_fn_rejects_ids_that_slugify_to_the_same_value() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+
+        handler
+            .register_fn(noop_fn(&client, "Send Email"))
+            .expect("first function should register");
+        let err = handler
+            .register_fn(noop_fn(&client, "send-email"))
+            .expect_err("colliding function should be rejected");
+
+        assert_eq!(
+            err,
+            RegisterError::DuplicateId {
+                slug: "test-app-send-email".to_string(),
+                function: "function \"send-email\"".to_string(),
+                existing: "function \"Send Email\"".to_string(),
+            }
+        );
+        assert_eq!(handler.funcs().len(), 1);
+    }
+
+    #[test]
+    fn register_fns_rejects_collision_with_generated_failure_handler_atomically() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+
+        let with_failure = noop_fn(&client, "first").on_failure(
+            |_input: Input<FunctionFailureEvent<FirstEvent>>, _step| async move { Ok(json!({})) },
+        );
+        let err = handler
+            .register_fns(vec![
+                noop_fn(&client, "other").into(),
+                with_failure.into(),
+                noop_fn(&client, "first-failure").into(),
+            ])
+            .expect_err("collision with failure handler should be rejected");
+
+        assert_eq!(
+            err.to_string(),
+            "function \"first-failure\" collides with the on_failure handler of function \"first\": both resolve to ID test-app-first-failure"
+        );
+        assert!(handler.funcs().is_empty());
+    }
+
+    #[test]
+    fn strict_ids_rejects_non_canonical_ids() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client).strict_ids(true);
+
+        let err = handler
+            .register_fn(noop_fn(&client, "Send Email"))
+            .expect_err("non-canonical ID should be rejected");
+        assert_eq!(
+            err,
+            RegisterError::NonCanonicalId {
+                id: "Send Email".to_string(),
+                slug: "send-email".to_string(),
+            }
+        );
+
+        let with_failure = noop_fn(&client, "send-email").on_failure(
+            |_input: Input<FunctionFailureEvent<FirstEvent>>, _step| async move { Ok(json!({})) },
+        );
+        handler
+            .register_fn(with_failure)
+            .expect("canonical ID should register");
+        assert_eq!(handler.funcs().len(), 2);
+    }
+
+    #[test]
+    fn app_and_group_defaults_fill_unset_function_options() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client).defaults(
+            FunctionDefaults::new()
+                .retries(5)
+                .timeouts(FunctionTimeouts::new().start("1m").finish("1h"))
+                .concurrency(FunctionConcurrency::limit(10))
+                .cancel(FunctionCancel::new("app/user.deleted")),
+        );
+
+        handler
+            .register_fn(noop_fn(&client, "plain"))
+            .expect("function should register");
+        handler
+            .group(
+                FunctionDefaults::new()
+                    .retries(1)
+                    .cancel(FunctionCancel::new("app/account.closed")),
+            )
+            .register_fn(
+                client
+                    .create_function(
+                        FunctionOpts::new("overridden")
+                            .concurrency(FunctionConcurrency::limit(2))
+                            .timeouts(FunctionTimeouts::new().finish("5m"))
+                            .cancel(
+                                FunctionCancel::new("app/user.deleted")
+                                    .if_exp("event.data.id == async.data.id"),
+                            ),
+                        Trigger::event("test/first"),
+                        |_input: Input<FirstEvent>, _step| async move { Ok::<_, Error>(json!({})) },
+                    )
+                    .on_failure(
+                        |_input: Input<FunctionFailureEvent<FirstEvent>>, _step| async move {
+                            Ok(json!({}))
+                        },
+                    ),
+            )
+            .expect("grouped function should register");
+
+        let payload = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect("sync payload should serialize");
+        let function = |id: &str| {
+            serde_json::to_value(
+                payload
+                    .functions
+                    .iter()
+                    .find(|function| function.id == id)
+                    .expect("function should be synced"),
+            )
+            .expect("function should serialize")
+        };
+
+        let plain = function("test-app-plain");
+        assert_eq!(plain["steps"]["step"]["retries"]["attempts"], json!(5));
+        assert_eq!(plain["timeouts"], json!({ "start": "1m", "finish": "1h" }));
+        assert_eq!(plain["concurrency"], json!(10));
+        assert_eq!(plain["cancel"], json!([{ "event": "app/user.deleted" }]));
+
+        let overridden = function("test-app-overridden");
+        assert_eq!(overridden["steps"]["step"]["retries"]["attempts"], json!(1));
+        assert_eq!(
+            overridden["timeouts"],
+            json!({ "start": "1m", "finish": "5m" })
+        );
+        assert_eq!(overridden["concurrency"], json!(2));
+        assert_eq!(
+            overridden["cancel"],
+            json!([
+                { "event": "app/user.deleted", "if": "event.data.id == async.data.id" },
+                { "event": "app/account.closed" },
+            ])
+        );
+
+        let failure = function("test-app-overridden-failure");
+        assert_eq!(failure["steps"]["step"]["retries"]["attempts"], json!(0));
+        assert!(failure.get("timeouts").is_none());
+        assert!(failure.get("concurrency").is_none());
+    }
+
+    #[test]
+    fn app_defaults_set_after_registration_apply_to_every_function() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+        handler
+            .register_fns(vec![noop_fn(&client, "early").into()])
+            .expect("function should register");
+
+        let mut handler = handler.defaults(FunctionDefaults::new().retries(7));
+        handler
+            .register_fns(vec![noop_fn(&client, "late").into()])
+            .expect("function should register");
+
+        let payload = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect("sync payload should serialize");
+        for function in &payload.functions {
+            assert_eq!(
+                function.steps["step"].retries.attempts, 7,
+                "{} should use the app default",
+                function.id
+            );
+        }
+        assert_eq!(payload.functions.len(), 2);
+    }
+
+    #[test]
+    fn sync_payload_includes_internal_failure_handler_function() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+
+        let func: ServableFn<FirstEvent, Error> = client
+            .create_function(
+                FunctionOpts::new("first"),
+                Trigger::event("test/first"),
+                |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
+            )
+            .on_failure(
+                |input: Input<FunctionFailureEvent<FirstEvent>>, _step| async move {
+                    Ok(json!({
+                        "failed": input.event.data.function_id,
+                    }))
+                },
+            );
+
+        handler.register_fn(func).expect("function should register");
+
+        let payload = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect("sync payload should serialize");
+        let functions = payload.functions;
+        let function_ids: HashSet<String> = functions
+            .iter()
+            .map(|function| function.id.clone())
+            .collect();
+
+        assert_eq!(function_ids.len(), 2);
+        assert!(function_ids.contains("test-app-first"));
+        assert!(function_ids.contains("test-app-first-failure"));
+
+        let failure_function = functions
+            .into_iter()
+            .find(|function| function.id == "test-app-first-failure")
+            .expect("failure function should be present");
+
+        assert_eq!(failure_function.name, "test-app-first (failure)");
+        assert_eq!(
+            failure_function.triggers,
+            vec![Trigger::EventTrigger {
+                event: "inngest/function.failed".to_string(),
+                expression: Some("event.data.function_id == \"test-app-first\"".to_string()),
+            }]
+        );
+        assert_eq!(failure_function.steps["step"].retries.attempts, 0);
+        assert!(failure_function.concurrency.is_none());
+        assert!(failure_function.singleton.is_none());
+    }
+
+    #[test]
+    fn sync_payload_rejects_batch_configs_outside_spec_limits() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+
+        let func: ServableFn<FirstEvent, Error> = client.create_function(
+            FunctionOpts::new("first")
+                .batch_events(FunctionBatchEvents::new(101, Duration::from_secs(0))),
+            Trigger::event("test/first"),
+            |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
+        );
+
+        handler.register_fn(func).expect("function should register");
+
+        let error = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect_err("invalid batch config should be rejected");
+
+        assert_basic_error(error, "batchEvents.maxSize must be between 1 and 100");
+    }
+
+    #[test]
+    fn sync_payload_includes_every_trigger() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+
+        let func: ServableFn<Value, Error> = client.create_function(
+            FunctionOpts::new("multi"),
+            [
+                Trigger::event("test/first"),
+                Trigger::event("test/second").expr("event.data.count > 1"),
+                Trigger::cron("0 * * * *"),
+            ],
+            |_input: Input<Value>, _step| async move { Ok(json!({ "ok": true })) },
+        );
+
+        handler.register_fn(func).expect("function should register");
+
+        let payload = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect("sync payload should serialize");
+
+        assert_eq!(
+            serde_json::to_value(&payload.functions[0].triggers).unwrap(),
+            json!([
+                { "event": "test/first", "expression": null },
+                { "event": "test/second", "expression": "event.data.count > 1" },
+                { "cron": "0 * * * *" }
+            ])
+        );
+    }
+
+    #[test]
+    fn sync_payload_rejects_more_than_ten_triggers() {
+        let client = Inngest::new("test-app");
+        let mut handler = Handler::new(&client);
+
+        let triggers = (0..11)
+            .map(|i| Trigger::event(&format!("test/event-{i}")))
+            .collect::<Vec<_>>();
+        let func: ServableFn<Value, Error> = client.create_function(
+            FunctionOpts::new("too-many"),
+            triggers,
+            |_input: Input<Value>, _step| async move { Ok(json!({ "ok": true })) },
+        );
+
+        handler.register_fn(func).expect("function should register");
+
+        let error = handler
+            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+            .expect_err("too many triggers should be rejected");
+
+        assert_basic_error(
+            error,
+            "function test-app-too-many triggers must include between 1 and 10 triggers, got 11",
+        );
+    }
+
+    #[test]
+    fn sync_payload_rejects_event_names_that_do_not_match_triggers() {
+        let client = Inngest::new("test-app");
+        let enum_fn = |id: &str, triggers: Vec<Trigger>| -> ServableFn<TestEvents, Error> {
+            client
+                .create_function(
+                    FunctionOpts::new(id),
+                    triggers,
+                    |_input: Input<TestEvents>, _step| async move { Ok(json!({})) },
+                )
+                .dispatch_by_event_name()
+        };
+        let cases = [
+            (
+                enum_fn(
+                    "extra-trigger",
+                    vec![
+                        Trigger::event("test/first"),
+                        Trigger::event("test/second"),
+                        Trigger::event("test/third"),
+                    ],
+                ),
+                "function test-app-extra-trigger is triggered by test/third, which is not listed in its event names",
+            ),
+            (
+                enum_fn("missing-trigger", vec![Trigger::event("test/first")]),
+                "function test-app-missing-trigger lists event name test/second but has no trigger for it",
+            ),
+        ];
+
+        for (func, expected) in cases {
+            let mut handler = Handler::new(&client);
+            handler.register_fn(func).expect("function should register");
+
+            let error = handler
+                .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+                .expect_err("mismatched event names should be rejected");
+
+            assert_basic_error(error, expected);
+        }
 
-        let payload = handler
+        let mut handler = Handler::new(&client);
+        handler
+            .register_fn(enum_fn("wildcard", vec![Trigger::event("test/*")]))
+            .expect("function should register");
+        handler
             .sync_payload(&Headers::from(HeaderMap::new()), "axum")
-            .expect("sync payload should serialize");
-        let function = payload
-            .functions
-            .into_iter()
-            .next()
-            .expect("registered function should be present");
-        let serialized = serde_json::to_value(function).expect("function should serialize");
-
-        assert_eq!(serialized["idempotency"], json!("event.data.id"));
-        assert!(serialized.get("rateLimit").is_none());
+            .expect("a wildcard trigger covering every name should sync");
     }
 
     #[test]
-    fn sync_payload_includes_internal_failure_handler_function() {
+    fn sync_payload_serializes_typed_cron_triggers() {
         let client = Inngest::new("test-app");
         let mut handler = Handler::new(&client);
 
-        let func: ServableFn<FirstEvent, Error> = client
-            .create_function(
-                FunctionOpts::new("first"),
-                Trigger::event("test/first"),
-                |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
-            )
-            .on_failure(
-                |input: Input<FunctionFailureEvent<FirstEvent>>, _step| async move {
-                    Ok(json!({
-                        "failed": input.event.data.function_id,
-                    }))
-                },
-            );
+        let func: ServableFn<Value, Error> = client.create_function(
+            FunctionOpts::new("cron"),
+            crate::cron::Cron::new("0 12 * * FRI")
+                .timezone("Europe/Paris")
+                .jitter(Duration::from_secs(30)),
+            |_input: Input<Value>, _step| async move { Ok(json!({ "ok": true })) },
+        );
 
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let payload = handler
             .sync_payload(&Headers::from(HeaderMap::new()), "axum")
             .expect("sync payload should serialize");
-        let functions = payload.functions;
-        let function_ids: HashSet<String> = functions
-            .iter()
-            .map(|function| function.id.clone())
-            .collect();
-
-        assert_eq!(function_ids.len(), 2);
-        assert!(function_ids.contains("test-app-first"));
-        assert!(function_ids.contains("test-app-first-failure"));
-
-        let failure_function = functions
-            .into_iter()
-            .find(|function| function.id == "test-app-first-failure")
-            .expect("failure function should be present");
 
-        assert_eq!(failure_function.name, "test-app-first (failure)");
         assert_eq!(
-            failure_function.triggers,
-            vec![Trigger::EventTrigger {
-                event: "inngest/function.failed".to_string(),
-                expression: Some("event.data.function_id == \"test-app-first\"".to_string()),
-            }]
+            serde_json::to_value(&payload.functions[0].triggers).unwrap(),
+            json!([{ "cron": "TZ=Europe/Paris 0 12 * * FRI", "jitter": "30s" }])
         );
-        assert_eq!(failure_function.steps["step"].retries.attempts, 0);
-        assert!(failure_function.concurrency.is_none());
-        assert!(failure_function.singleton.is_none());
     }
 
     #[test]
-    fn sync_payload_rejects_batch_configs_outside_spec_limits() {
-        let client = Inngest::new("test-app");
-        let mut handler = Handler::new(&client);
+    fn sync_payload_rejects_invalid_cron_triggers() {
+        for (trigger, expected) in [
+            (
+                Trigger::cron("0 25 * * *"),
+                "function test-app-cron cron trigger 0 25 * * *: cron hour value \"25\" must be between 0 and 23",
+            ),
+            (
+                Trigger::cron("0 * * * *").expr("event.data.ok"),
+                "function test-app-cron cron trigger 0 * * * * does not support expressions",
+            ),
+        ] {
+            let client = Inngest::new("test-app");
+            let mut handler = Handler::new(&client);
+            let func: ServableFn<Value, Error> = client.create_function(
+                FunctionOpts::new("cron"),
+                trigger,
+                |_input: Input<Value>, _step| async move { Ok(json!({ "ok": true })) },
+            );
 
-        let func: ServableFn<FirstEvent, Error> = client.create_function(
-            FunctionOpts::new("first")
-                .batch_events(FunctionBatchEvents::new(101, Duration::from_secs(0))),
-            Trigger::event("test/first"),
-            |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
-        );
+            handler.register_fn(func).expect("function should register");
 
-        handler.register_fn(func);
+            let error = handler
+                .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+                .expect_err("invalid cron trigger should be rejected");
 
-        let error = handler
-            .sync_payload(&Headers::from(HeaderMap::new()), "axum")
-            .expect_err("invalid batch config should be rejected");
+            assert_basic_error(error, expected);
+        }
+    }
 
-        assert_basic_error(error, "batchEvents.maxSize must be between 1 and 100");
+    #[test]
+    fn sync_payload_rejects_invalid_expressions() {
+        let cases: Vec<(FunctionOpts, Trigger, &str)> = vec![
+            (
+                FunctionOpts::new("exprs"),
+                Trigger::event("test/first").expr("event.data.ok = true"),
+                "function test-app-exprs trigger test/first expression: unexpected character `=` at position 15, did you mean `==`?",
+            ),
+            (
+                FunctionOpts::new("exprs")
+                    .rate_limit(FunctionRateLimit::new(1, "1m").key("user.id")),
+                Trigger::event("test/first"),
+                "function test-app-exprs rateLimit.key: unknown identifier `user` at position 1; expressions can only reference event",
+            ),
+            (
+                FunctionOpts::new("exprs").concurrency(FunctionConcurrency::keyed(vec![
+                    FunctionConcurrencyOption::new(1).key("async.data.id"),
+                ])),
+                Trigger::event("test/first"),
+                "function test-app-exprs concurrency.key: unknown identifier `async` at position 1; expressions can only reference event",
+            ),
+            (
+                FunctionOpts::new("exprs")
+                    .cancel(FunctionCancel::new("test/cancel").if_exp("event.data.id == async.data.id &&")),
+                Trigger::event("test/first"),
+                "function test-app-exprs cancel.if: unexpected end of expression at position 34",
+            ),
+        ];
+
+        for (opts, trigger, expected) in cases {
+            let client = Inngest::new("test-app");
+            let mut handler = Handler::new(&client);
+            let func: ServableFn<Value, Error> =
+                client.create_function(opts, trigger, |_input: Input<Value>, _step| async move {
+                    Ok(json!({ "ok": true }))
+                });
+
+            handler.register_fn(func).expect("function should register");
+
+            let error = handler
+                .sync_payload(&Headers::from(HeaderMap::new()), "axum")
+                .expect_err("invalid expression should be rejected");
+
+            assert_basic_error(error, expected);
+        }
     }
 
     #[test]
@@ -1538,7 +3367,7 @@ mod tests {
             |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
         );
 
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let error = handler
             .sync_payload(&Headers::from(HeaderMap::new()), "axum")
@@ -1562,7 +3391,7 @@ mod tests {
             |_input: Input<FirstEvent>, _step| async move { Ok(json!({ "ok": true })) },
         );
 
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let error = handler
             .sync_payload(&Headers::from(HeaderMap::new()), "axum")
@@ -1593,7 +3422,7 @@ mod tests {
                 },
             );
 
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let payload = handler
             .sync_payload(&Headers::from(HeaderMap::new()), "axum")
@@ -1864,10 +3693,6 @@ mod tests {
                             .timeout(Duration::from_secs(30)),
                     )
                     .idempotency("event.data.id")
-                    .rate_limit(
-                        FunctionRateLimit::new(5, Duration::from_secs(60))
-                            .key("event.data.user_id"),
-                    )
                     .batch_events(
                         FunctionBatchEvents::new(10, Duration::from_secs(45))
                             .key("event.data.account_id"),
@@ -1888,10 +3713,6 @@ mod tests {
                             .key("event.data.region")
                             .burst(2),
                     )
-                    .singleton(
-                        FunctionSingleton::new(FunctionSingletonMode::Cancel)
-                            .key("event.data.workflow_id"),
-                    )
                     .timeouts(
                         FunctionTimeouts::new()
                             .start(Duration::from_secs(30))
@@ -1905,7 +3726,7 @@ mod tests {
                     Ok(json!({ "failed": input.event.data.function_id }))
                 },
             );
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let result = handler
             .sync(
@@ -1973,13 +3794,7 @@ mod tests {
                 "burst": 2
             })
         );
-        assert_eq!(
-            main_function["singleton"],
-            json!({
-                "key": "event.data.workflow_id",
-                "mode": "cancel"
-            })
-        );
+        assert!(main_function.get("singleton").is_none());
         assert_eq!(
             main_function["timeouts"],
             json!({
@@ -2067,6 +3882,294 @@ mod tests {
         assert_eq!(records[1].env, Some("branch".to_string()));
     }
 
+    async fn wait_for_sync_records(
+        records: &Arc<Mutex<Vec<SyncRequestRecord>>>,
+        count: usize,
+    ) -> Vec<SyncRequestRecord> {
+        for _ in 0..100 {
+            {
+                let records = records.lock().await;
+                if records.len() >= count {
+                    return records.clone();
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("expected {count} sync requests");
+    }
+
+    fn synced_function_ids(record: &SyncRequestRecord) -> Vec<String> {
+        let mut ids: Vec<String> = record.body["functions"]
+            .as_array()
+            .expect("sync body should list functions")
+            .iter()
+            .map(|function| function["id"].as_str().unwrap_or_default().to_string())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn live_handler_resyncs_when_functions_are_added_or_removed() {
+        let (origin, records) = spawn_sync_server(vec![]).await;
+        let client = Inngest::new("test-app").dev(&origin);
+        let handler = Arc::new(Handler::new(&client));
+
+        handler
+            .add_fn(noop_fn(&client, "first"))
+            .expect("function should register before the first sync");
+        assert_eq!(handler.resync().await.map(|res| res.is_ok()), None);
+
+        handler
+            .sync(
+                &Headers::from(HeaderMap::new()),
+                &SyncQueryParams { deploy_id: None },
+                "axum",
+            )
+            .await
+            .expect("sync should succeed");
+        assert_eq!(wait_for_sync_records(&records, 1).await.len(), 1);
+
+        let with_failure = noop_fn(&client, "second").on_failure(
+            |_input: Input<FunctionFailureEvent<FirstEvent>>, _step| async move { Ok(json!({})) },
+        );
+        handler
+            .add_fn(with_failure)
+            .expect("function should register on a live handler");
+        let synced = wait_for_sync_records(&records, 2).await;
+        assert_eq!(
+            synced_function_ids(&synced[1]),
+            vec![
+                "test-app-first".to_string(),
+                "test-app-second".to_string(),
+                "test-app-second-failure".to_string(),
+            ]
+        );
+
+        assert!(handler.remove_fn("second"));
+        let synced = wait_for_sync_records(&records, 3).await;
+        assert_eq!(
+            synced_function_ids(&synced[2]),
+            vec!["test-app-first".to_string()]
+        );
+
+        assert!(!handler.remove_fn("second"));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(records.lock().await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn live_handler_resyncs_one_change_at_a_time() {
+        let (origin, records, max_in_flight) =
+            spawn_slow_sync_server(vec![], Duration::from_millis(100)).await;
+        let client = Inngest::new("test-app").dev(&origin);
+        let mut handler = Handler::new(&client);
+        handler
+            .register_fn(noop_fn(&client, "first"))
+            .expect("function should register");
+        let handler = Arc::new(handler);
+        handler
+            .sync(
+                &Headers::from(HeaderMap::new()),
+                &SyncQueryParams::default(),
+                "axum",
+            )
+            .await
+            .expect("sync should succeed");
+
+        handler
+            .add_fn(noop_fn(&client, "second"))
+            .expect("function should register on a live handler");
+        assert!(handler.remove_fn("second"));
+
+        let synced = wait_for_sync_records(&records, 3).await;
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            synced_function_ids(&synced[2]),
+            vec!["test-app-first".to_string()]
+        );
+    }
+
+    #[test]
+    fn remove_fn_matches_hooks_by_slugified_parent_id() {
+        let client = Inngest::new("test-app");
+        let handler = Arc::new(Handler::new(&client));
+        let func = noop_fn(&client, "Send Email").on_failure(
+            |_input: Input<FunctionFailureEvent<FirstEvent>>, _step| async move { Ok(json!({})) },
+        );
+        handler.add_fn(func).expect("function should register");
+        assert_eq!(handler.funcs().len(), 2);
+
+        assert!(handler.remove_fn("send-email"));
+        assert!(handler.funcs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn sync_on_start_retries_with_backoff_and_reports_status() {
+        let (origin, records) = spawn_sync_server(vec![
+            (StatusCode::BAD_GATEWAY, "{}".to_string()),
+            (StatusCode::SERVICE_UNAVAILABLE, "{}".to_string()),
+        ])
+        .await;
+        let client = Inngest::new("test-app").dev(&origin);
+        let mut handler = Handler::new(&client)
+            .sync_retries(3)
+            .sync_retry_backoff(Duration::from_millis(10));
+        handler
+            .register_fn(noop_fn(&client, "first"))
+            .expect("function should register");
+        let handler = Arc::new(handler);
+        assert_eq!(handler.sync_status(), SyncStatus::NotSynced);
+
+        let result = handler
+            .sync_on_start("https://app.example.com")
+            .await
+            .expect("sync task should not panic");
+
+        assert!(result.is_ok(), "sync should succeed after retries");
+        assert!(matches!(
+            handler.sync_status(),
+            SyncStatus::Synced { modified: true, .. }
+        ));
+        let records = records.lock().await;
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[2].body["url"],
+            json!("https://app.example.com/api/inngest")
+        );
+        assert_eq!(
+            records[2].body["functions"][0]["steps"]["step"]["runtime"]["url"],
+            json!("https://app.example.com/api/inngest?fnId=test-app-first&stepId=step")
+        );
+    }
+
+    #[tokio::test]
+    async fn sync_on_start_does_not_retry_rejected_syncs() {
+        let (origin, records) = spawn_sync_server(vec![(
+            StatusCode::UNAUTHORIZED,
+            "{\"error\":\"invalid signing key\"}".to_string(),
+        )])
+        .await;
+        let client = Inngest::new("test-app").dev(&origin);
+        let handler = Arc::new(
+            Handler::new(&client)
+                .sync_retries(3)
+                .sync_retry_backoff(Duration::from_millis(10)),
+        );
+
+        let result = handler
+            .sync_on_start("https://app.example.com")
+            .await
+            .expect("sync task should not panic");
+
+        assert!(result.is_err());
+        match handler.sync_status() {
+            SyncStatus::Failed {
+                attempts, error, ..
+            } => {
+                assert_eq!(attempts, 1);
+                assert!(error.contains("status 401"), "{error}");
+            }
+            other => panic!("expected failed sync status, got {other:?}"),
+        }
+        assert_eq!(records.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn sync_on_start_reports_failure_after_exhausting_retries() {
+        let (origin, records) = spawn_sync_server(vec![
+            (StatusCode::BAD_GATEWAY, "{}".to_string()),
+            (StatusCode::BAD_GATEWAY, "{}".to_string()),
+        ])
+        .await;
+        let client = Inngest::new("test-app").dev(&origin);
+        let handler = Arc::new(
+            Handler::new(&client)
+                .sync_retries(1)
+                .sync_retry_backoff(Duration::from_millis(10)),
+        );
+
+        let result = handler
+            .sync_on_start("https://app.example.com/custom/inngest")
+            .await
+            .expect("sync task should not panic");
+
+        assert!(result.is_err());
+        match handler.sync_status() {
+            SyncStatus::Failed {
+                attempts, error, ..
+            } => {
+                assert_eq!(attempts, 2);
+                assert!(error.contains("status 502"), "{error}");
+            }
+            other => panic!("expected failed sync status, got {other:?}"),
+        }
+        let records = records.lock().await;
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].body["url"],
+            json!("https://app.example.com/custom/inngest")
+        );
+
+        match handler
+            .sync_on_start("not a url")
+            .await
+            .expect("sync task should not panic")
+        {
+            Err(err) => assert!(err.starts_with("invalid public URL"), "{err}"),
+            Ok(_) => panic!("invalid URLs should fail without retrying"),
+        }
+    }
+
+    #[tokio::test]
+    async fn sync_and_hydration_use_the_injected_http_client() {
+        let mut default_headers = reqwest::header::HeaderMap::new();
+        default_headers.insert(TEST_TRANSPORT_HEADER, "custom".parse().unwrap());
+        let http = reqwest::Client::builder()
+            .default_headers(default_headers)
+            .build()
+            .expect("custom client should build");
+
+        let (sync_origin, sync_records) = spawn_sync_server(vec![]).await;
+        let client = Inngest::new("test-app")
+            .dev(&sync_origin)
+            .http_client(http.clone());
+        let (handler, _fn_id) = registered_handler(client, None, None);
+        handler
+            .sync(
+                &Headers::from(HeaderMap::new()),
+                &SyncQueryParams { deploy_id: None },
+                "axum",
+            )
+            .await
+            .expect("sync should succeed");
+        assert_eq!(
+            sync_records.lock().await[0].transport,
+            Some("custom".to_string())
+        );
+
+        let (run_origin, run_records) = spawn_run_api_server(vec![], vec![]).await;
+        let client = Inngest::new("test-app").dev(&run_origin).http_client(http);
+        let (handler, fn_id) = registered_handler(client, None, None);
+        let body = use_api_body();
+        handler
+            .run(
+                &Headers::from(HeaderMap::new()),
+                &run_query(fn_id),
+                &body.to_string(),
+                &body,
+            )
+            .await
+            .expect("use_api payloads should hydrate");
+
+        let run_records = run_records.lock().await;
+        assert_eq!(run_records.len(), 2);
+        assert!(run_records
+            .iter()
+            .all(|record| record.transport == Some("custom".to_string())));
+    }
+
     #[tokio::test]
     async fn handler_passes_requested_step_id_to_function_context() {
         let client = Inngest::new("test-app").dev("1");
@@ -2080,7 +4183,7 @@ mod tests {
                 },
             );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let body = event_body("test/first", json!({ "message": "hello" }));
         let response = handler
@@ -2100,6 +4203,58 @@ mod tests {
         assert_eq!(response.body, json!({ "step_id": "custom-step" }));
     }
 
+    #[tokio::test]
+    async fn handler_passes_fired_trigger_to_function_context() {
+        let client = Inngest::new("test-app").dev("1");
+        let mut handler = Handler::new(&client);
+        let func: ServableFn<Value, Error> = client.create_function(
+            FunctionOpts::new("fired-trigger"),
+            vec![
+                Trigger::event("test/first"),
+                Trigger::event("billing/*"),
+                Trigger::cron("0 * * * *"),
+                Trigger::cron("30 2 * * *"),
+            ],
+            |input: Input<Value>, _step| async move {
+                Ok(serde_json::to_value(input.ctx.trigger).unwrap())
+            },
+        );
+        let fn_id = func.slug();
+        handler.register_fn(func).expect("function should register");
+
+        for (name, data, expected) in [
+            (
+                "billing/invoice.paid",
+                json!({}),
+                json!({ "event": "billing/*", "expression": null }),
+            ),
+            (
+                "inngest/scheduled.timer",
+                json!({ "cron": "30 2 * * *" }),
+                json!({ "cron": "30 2 * * *" }),
+            ),
+            (
+                "inngest/scheduled.timer",
+                json!({ "cron": "15 * * * *" }),
+                Value::Null,
+            ),
+            ("test/unknown", json!({}), Value::Null),
+        ] {
+            let body = event_body(name, data);
+            let response = handler
+                .run(
+                    &Headers::from(HeaderMap::new()),
+                    &run_query(fn_id.clone()),
+                    &body.to_string(),
+                    &body,
+                )
+                .await
+                .expect("handler should run the function");
+
+            assert_eq!(response.body, expected);
+        }
+    }
+
     #[tokio::test]
     async fn targeted_step_requests_execute_the_matching_hashed_step() {
         let client = Inngest::new("test-app").dev("1");
@@ -2117,7 +4272,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let body = event_body("test/first", json!({ "message": "hello" }));
         let targeted_step_id = hash_step_id("hello");
@@ -2157,7 +4312,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let body = event_body("test/first", json!({ "message": "hello" }));
         let missing_step_id = hash_step_id("other");
@@ -2208,7 +4363,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let body = event_body_with_ctx(
             "test/first",
@@ -2258,7 +4413,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let targeted_step_id = hash_step_id("hello");
         let body = event_body_with_ctx(
@@ -2312,7 +4467,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let body = event_body("test/first", json!({ "message": "hello" }));
         let response = handler
@@ -2354,7 +4509,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let targeted_step_id = hash_step_id("second");
         let first_step_id = hash_step_id("first");
@@ -2414,7 +4569,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let missing_step_id = hash_step_id("other");
         let body = event_body_with_ctx(
@@ -2470,7 +4625,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let body = event_body("test/first", json!({ "message": "hello" }));
         let response = handler
@@ -2513,7 +4668,7 @@ mod tests {
             },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let body = json!({
             "ctx": {
@@ -2606,7 +4761,7 @@ mod tests {
             |input: Input<FirstEvent>, _step| async move { Ok(json!(input.event.data.message)) },
         );
         let fn_id = func.slug();
-        handler.register_fn(func);
+        handler.register_fn(func).expect("function should register");
 
         let body = use_api_body();
         let response = handler
@@ -2788,7 +4943,9 @@ mod tests {
             },
         );
         let fn_id = first_fn.slug();
-        handler.register_fn(first_fn);
+        handler
+            .register_fn(first_fn)
+            .expect("function should register");
 
         (handler, fn_id)
     }
@@ -2841,27 +4998,32 @@ mod tests {
     async fn spawn_sync_server(
         responses: Vec<(StatusCode, String)>,
     ) -> (String, Arc<Mutex<Vec<SyncRequestRecord>>>) {
+        let (origin, records, _) = spawn_slow_sync_server(responses, Duration::ZERO).await;
+        (origin, records)
+    }
+
+    /// Like `spawn_sync_server`, but waits `delay` before responding and
+    /// tracks the most sync requests that were in flight at once.
+    async fn spawn_slow_sync_server(
+        responses: Vec<(StatusCode, String)>,
+        delay: Duration,
+    ) -> (String, Arc<Mutex<Vec<SyncRequestRecord>>>, Arc<AtomicUsize>) {
         let records = Arc::new(Mutex::new(Vec::new()));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
         let state = SyncServerState {
             records: Arc::clone(&records),
             responses: Arc::new(Mutex::new(VecDeque::from(responses))),
+            delay,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            max_in_flight: Arc::clone(&max_in_flight),
         };
 
-        let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
-        let addr = listener.local_addr().expect("listener addr should exist");
         let app = Router::new()
             .route("/fn/register", post(record_sync_request))
             .with_state(state);
+        let origin = spawn_test_server(app);
 
-        tokio::spawn(async move {
-            axum::Server::from_tcp(listener)
-                .expect("server should bind")
-                .serve(app.into_make_service())
-                .await
-                .expect("server should serve");
-        });
-
-        (format!("http://{}", addr), records)
+        (origin, records, max_in_flight)
     }
 
     async fn spawn_run_api_server(
@@ -2888,6 +5050,7 @@ mod tests {
                 query: uri.query().map(|query| query.to_string()),
                 req_version: header_value(&headers, header::INNGEST_REQ_VERSION),
                 sdk: header_value(&headers, header::INNGEST_SDK),
+                transport: header_value(&headers, TEST_TRANSPORT_HEADER),
             });
 
             state
@@ -2911,6 +5074,7 @@ mod tests {
                 query: uri.query().map(|query| query.to_string()),
                 req_version: header_value(&headers, header::INNGEST_REQ_VERSION),
                 sdk: header_value(&headers, header::INNGEST_SDK),
+                transport: header_value(&headers, TEST_TRANSPORT_HEADER),
             });
 
             state
@@ -2927,22 +5091,13 @@ mod tests {
             batch_responses: Arc::new(Mutex::new(VecDeque::from(batch_responses))),
             records: Arc::clone(&records),
         };
-        let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
-        let addr = listener.local_addr().expect("listener addr should exist");
         let app = Router::new()
             .route("/v0/runs/run-1/batch", get(fetch_batch))
             .route("/v0/runs/run-1/actions", get(fetch_actions))
             .with_state(state);
+        let origin = spawn_test_server(app);
 
-        tokio::spawn(async move {
-            axum::Server::from_tcp(listener)
-                .expect("server should bind")
-                .serve(app.into_make_service())
-                .await
-                .expect("server should serve");
-        });
-
-        (format!("http://{}", addr), records)
+        (origin, records)
     }
 
     async fn record_sync_request(
@@ -2951,6 +5106,9 @@ mod tests {
         uri: Uri,
         body: String,
     ) -> (StatusCode, String) {
+        let in_flight = state.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        state.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+
         state.records.lock().await.push(SyncRequestRecord {
             authorization: header_value(&headers, "authorization"),
             body: serde_json::from_str(&body).expect("sync request body should be valid JSON"),
@@ -2959,8 +5117,12 @@ mod tests {
             query: uri.query().map(|query| query.to_string()),
             req_version: header_value(&headers, header::INNGEST_REQ_VERSION),
             sdk: header_value(&headers, header::INNGEST_SDK),
+            transport: header_value(&headers, TEST_TRANSPORT_HEADER),
         });
 
+        tokio::time::sleep(state.delay).await;
+        state.in_flight.fetch_sub(1, Ordering::SeqCst);
+
         state
             .responses
             .lock()
@@ -1,14 +1,19 @@
 pub mod client;
-pub(crate) mod config;
+pub mod config;
+pub mod cron;
 pub mod event;
+pub mod expression;
+pub mod extensions;
 pub mod function;
 pub mod group;
 pub mod handler;
 pub mod header;
 pub mod result;
+pub mod run;
 pub mod sdk;
 pub mod serve;
 pub mod signature;
 pub mod step_tool;
 pub(crate) mod utils;
 pub(crate) mod version;
+pub mod webhook;
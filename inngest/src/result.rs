@@ -1,6 +1,7 @@
 use std::{
     error::Error as StdError,
     fmt::{Debug, Display},
+    path::PathBuf,
     time::Duration,
 };
 
@@ -30,6 +31,8 @@ pub enum DevError {
     Basic(String),
     /// A memoized step error surfaced back through replay.
     Step(StepError),
+    /// A typed invoke that timed out or whose target function failed.
+    Invoke(Box<InvokeError>),
     /// Error that controls how the function will be retried
     RetryAt(RetryAfterError),
     /// Error that does not allow the function to be retried
@@ -167,6 +170,13 @@ impl IntoResponse for Error {
                 DevError::Step(err) => {
                     call_error_response(headers, StatusCode::BAD_REQUEST, true, None, err)
                 }
+                DevError::Invoke(err) => call_error_response(
+                    headers,
+                    StatusCode::BAD_REQUEST,
+                    true,
+                    None,
+                    err.into_step_error(),
+                ),
                 DevError::RetryAt(retry) => {
                     let retry_after = HeaderValue::from(retry.after.as_secs());
 
@@ -269,6 +279,264 @@ impl Display for StepError {
 
 impl StdError for StepError {}
 
+/// Error returned by the network methods on [`crate::client::Inngest`].
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request could not be sent or its response could not be read.
+    Network(reqwest::Error),
+    /// The API responded with a non-success status or reported an `error`.
+    Api {
+        status: u16,
+        body: String,
+        message: Option<String>,
+    },
+    /// The response body could not be decoded.
+    Decode {
+        body: String,
+        source: serde_json::Error,
+    },
+    /// The request could not be built from the provided input.
+    InvalidRequest(String),
+    /// The invoked run finished without completing successfully.
+    RunFailed { run_id: String, error: StepError },
+    /// Waiting on the API did not finish before the deadline.
+    Timeout(String),
+}
+
+impl ClientError {
+    /// Builds an API error from a non-success response, extracting the
+    /// `error` field from JSON bodies when present.
+    pub(crate) fn api(status: u16, body: String) -> Self {
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|value| value.get("error").and_then(Value::as_str).map(String::from));
+
+        Self::Api {
+            status,
+            body,
+            message,
+        }
+    }
+
+    /// Returns the HTTP status reported by the API, if any.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::Network(err) => err.status().map(|status| status.as_u16()),
+            Self::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw response body, if one was received.
+    pub fn body(&self) -> Option<&str> {
+        match self {
+            Self::Api { body, .. } | Self::Decode { body, .. } => Some(body),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` when sending the same request again may succeed.
+    ///
+    /// Network failures, timeouts, `408`, `429` and `5xx` responses are
+    /// considered retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network(err) => !err.is_builder(),
+            Self::Api { status, .. } => *status == 408 || *status == 429 || *status >= 500,
+            Self::Timeout(_) => true,
+            Self::Decode { .. } | Self::InvalidRequest(_) | Self::RunFailed { .. } => false,
+        }
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Network(err) => write!(f, "network error: {}", err),
+            Self::Api {
+                status,
+                message: Some(message),
+                ..
+            } => write!(f, "API error (status {}): {}", status, message),
+            Self::Api { status, body, .. } => write!(f, "API error (status {}): {}", status, body),
+            Self::Decode { source, .. } => write!(f, "error decoding API response: {}", source),
+            Self::InvalidRequest(message) => write!(f, "invalid request: {}", message),
+            Self::RunFailed { run_id, error } => write!(f, "run {} failed: {}", run_id, error),
+            Self::Timeout(message) => write!(f, "timed out: {}", message),
+        }
+    }
+}
+
+impl StdError for ClientError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Network(err) => Some(err),
+            Self::Decode { source, .. } => Some(source),
+            Self::RunFailed { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Errors raised when registering functions with a
+/// [`Handler`](crate::handler::Handler).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RegisterError {
+    /// Two functions resolve to the same synced ID, so one would silently
+    /// replace the other.
+    DuplicateId {
+        slug: String,
+        function: String,
+        existing: String,
+    },
+    /// Strict IDs are enabled and the function ID changes when slugified.
+    NonCanonicalId { id: String, slug: String },
+}
+
+impl Display for RegisterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateId {
+                slug,
+                function,
+                existing,
+            } => write!(
+                f,
+                "{} collides with {}: both resolve to ID {}",
+                function, existing, slug
+            ),
+            Self::NonCanonicalId { id, slug } => write!(
+                f,
+                "function ID {:?} changes to {:?} when slugified; use the slug as the ID or disable strict IDs",
+                id, slug
+            ),
+        }
+    }
+}
+
+impl StdError for RegisterError {}
+
+/// Errors raised while loading an [`InngestConfig`](crate::config::InngestConfig).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    Io { path: PathBuf, message: String },
+    /// The configuration file is not valid.
+    Parse { path: PathBuf, message: String },
+    /// An environment variable is set to a value that is not valid unicode.
+    InvalidEnv { var: &'static str },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, message } => {
+                write!(f, "error reading {}: {}", path.display(), message)
+            }
+            Self::Parse { path, message } => {
+                write!(f, "invalid config file {}: {}", path.display(), message)
+            }
+            Self::InvalidEnv { var } => {
+                write!(f, "environment variable {} is not valid unicode", var)
+            }
+        }
+    }
+}
+
+impl StdError for ConfigError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Network(err)
+    }
+}
+
+/// The error name Inngest reports when an invoked function does not finish
+/// before the invoke timeout.
+pub(crate) const INVOKE_TIMEOUT_ERROR_NAME: &str = "InngestInvokeTimeoutError";
+
+/// The outcome of a failed [`invoke_fn`](crate::step_tool::Step::invoke_fn) step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InvokeError {
+    /// The invoked function did not finish before the invoke timeout. The
+    /// function may still be running.
+    Timeout {
+        function_id: String,
+        error: StepError,
+    },
+    /// The invoked function ran and failed after exhausting its retries.
+    Failed {
+        function_id: String,
+        error: StepError,
+    },
+}
+
+impl InvokeError {
+    pub(crate) fn from_step_error(function_id: String, error: StepError) -> Self {
+        if error.name == INVOKE_TIMEOUT_ERROR_NAME {
+            Self::Timeout { function_id, error }
+        } else {
+            Self::Failed { function_id, error }
+        }
+    }
+
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Timeout { .. })
+    }
+
+    /// The fully qualified ID of the invoked function.
+    pub fn function_id(&self) -> &str {
+        match self {
+            Self::Timeout { function_id, .. } | Self::Failed { function_id, .. } => function_id,
+        }
+    }
+
+    /// The error reported by Inngest for the invoke step.
+    pub fn step_error(&self) -> &StepError {
+        match self {
+            Self::Timeout { error, .. } | Self::Failed { error, .. } => error,
+        }
+    }
+
+    fn into_step_error(self) -> StepError {
+        match self {
+            Self::Timeout { error, .. } | Self::Failed { error, .. } => error,
+        }
+    }
+}
+
+impl Display for InvokeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout { function_id, error } => write!(
+                f,
+                "timed out waiting for function {} to finish: {}",
+                function_id, error.message
+            ),
+            Self::Failed { function_id, error } => {
+                write!(f, "function {} failed: {}", function_id, error)
+            }
+        }
+    }
+}
+
+impl StdError for InvokeError {}
+
+impl From<ClientError> for DevError {
+    fn from(err: ClientError) -> Self {
+        match err {
+            ClientError::RunFailed { error, .. } => DevError::Step(error),
+            other => DevError::Basic(other.to_string()),
+        }
+    }
+}
+
+impl From<ClientError> for Error {
+    fn from(err: ClientError) -> Self {
+        Error::Dev(err.into())
+    }
+}
+
 /// A retriable developer error that asks Inngest to delay the next attempt.
 pub struct RetryAfterError {
     pub message: String,
@@ -447,4 +715,22 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn client_errors_classify_retryable_statuses() {
+        assert!(ClientError::api(429, String::new()).is_retryable());
+        assert!(ClientError::api(503, String::new()).is_retryable());
+        assert!(!ClientError::api(400, String::new()).is_retryable());
+        assert!(ClientError::Timeout("waiting".to_string()).is_retryable());
+        assert!(!ClientError::InvalidRequest("bad".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn client_errors_extract_api_messages() {
+        let err = ClientError::api(400, "{\"error\":\"bad event\"}".to_string());
+
+        assert_eq!(err.status(), Some(400));
+        assert_eq!(err.body(), Some("{\"error\":\"bad event\"}"));
+        assert_eq!(err.to_string(), "API error (status 400): bad event");
+    }
 }
@@ -0,0 +1,617 @@
+use std::{
+    fmt::{Display, Formatter},
+    time::{Duration, SystemTime},
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use slug::slugify;
+
+use crate::{client::Inngest, result::ClientError, utils::time};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The lifecycle status of a function run as reported by the REST API.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum RunStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    #[serde(other)]
+    Unknown,
+}
+
+impl RunStatus {
+    /// Returns `true` once the run can no longer make progress.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+impl Display for RunStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            Self::Queued => "Queued",
+            Self::Running => "Running",
+            Self::Completed => "Completed",
+            Self::Failed => "Failed",
+            Self::Cancelled => "Cancelled",
+            Self::Unknown => "Unknown",
+        };
+
+        write!(f, "{}", status)
+    }
+}
+
+/// A function run returned by the Inngest REST API.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Run {
+    pub run_id: String,
+    pub status: RunStatus,
+    #[serde(default)]
+    pub output: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_started_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ended_at: Option<String>,
+}
+
+/// A bulk cancellation created through the Inngest REST API.
+///
+/// Cancellations are processed asynchronously and the REST API does not
+/// document an endpoint for their progress; runs they cancel report
+/// [`RunStatus::Cancelled`] from [`Runs::get`].
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Cancellation {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_before: Option<String>,
+    #[serde(default, rename = "if", skip_serializing_if = "Option::is_none")]
+    pub if_exp: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiEnvelope<T> {
+    data: T,
+}
+
+/// Looks up and controls function runs through the Inngest REST API.
+///
+/// Requests are authenticated with the client's signing key and retried with
+/// the fallback signing key when the primary key is rejected.
+pub struct Runs<'a> {
+    client: &'a Inngest,
+}
+
+impl<'a> Runs<'a> {
+    pub(crate) fn new(client: &'a Inngest) -> Self {
+        Self { client }
+    }
+
+    /// Fetches a single run, including its status and output.
+    pub async fn get(&self, run_id: &str) -> Result<Run, ClientError> {
+        self.request(reqwest::Method::GET, &format!("/v1/runs/{run_id}"))
+            .await
+    }
+
+    /// Lists the runs triggered by the given event ID.
+    pub async fn list_for_event(&self, event_id: &str) -> Result<Vec<Run>, ClientError> {
+        self.request(reqwest::Method::GET, &format!("/v1/events/{event_id}/runs"))
+            .await
+    }
+
+    /// Polls until the given event has triggered at least one run and returns
+    /// the first one.
+    ///
+    /// Lookups that fail with `404` or a retryable error are polled again
+    /// until the deadline, since the event may still be being ingested.
+    pub async fn wait_for_event_run(
+        &self,
+        event_id: &str,
+        timeout: Duration,
+    ) -> Result<Run, ClientError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            // Runs are created asynchronously after the event is ingested.
+            match self.list_for_event(event_id).await {
+                Ok(runs) => {
+                    if let Some(run) = runs.into_iter().next() {
+                        return Ok(run);
+                    }
+                }
+                Err(err) if is_pending(&err) => {}
+                Err(err) => return Err(err),
+            }
+
+            if tokio::time::Instant::now() + POLL_INTERVAL > deadline {
+                return Err(ClientError::Timeout(format!(
+                    "waiting for event {} to start a run",
+                    event_id
+                )));
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Polls until the run reaches a finished status and returns it.
+    ///
+    /// Like [`Runs::wait_for_event_run`], `404` and retryable errors are
+    /// polled again until the deadline.
+    pub async fn wait_for_completion(
+        &self,
+        run_id: &str,
+        timeout: Duration,
+    ) -> Result<Run, ClientError> {
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        loop {
+            let last_seen = match self.get(run_id).await {
+                Ok(run) if run.status.is_finished() => return Ok(run),
+                Ok(run) => format!("last status was {}", run.status),
+                Err(err) if is_pending(&err) => format!("last error was {}", err),
+                Err(err) => return Err(err),
+            };
+
+            if tokio::time::Instant::now() + POLL_INTERVAL > deadline {
+                return Err(ClientError::Timeout(format!(
+                    "waiting for run {} to finish; {}",
+                    run_id, last_seen
+                )));
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Cancels a run that has not finished yet.
+    pub async fn cancel(&self, run_id: &str) -> Result<(), ClientError> {
+        let response = self
+            .send(reqwest::Method::DELETE, &format!("/v1/runs/{run_id}"), None)
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(ClientError::api(status.as_u16(), body));
+        }
+
+        Ok(())
+    }
+
+    /// Cancels every run of a function that started within the given time
+    /// range, optionally narrowed by an expression such as
+    /// `event.data.user_id == "user_123"`.
+    ///
+    /// `function_id` is the ID passed to [`crate::function::FunctionOpts::new`];
+    /// the app ID is taken from the client.
+    pub async fn cancel_bulk(
+        &self,
+        function_id: &str,
+        started_after: Option<SystemTime>,
+        started_before: SystemTime,
+        if_expr: Option<&str>,
+    ) -> Result<Cancellation, ClientError> {
+        let mut body = json!({
+            "app_id": self.client.app_id(),
+            "function_id": slugify(function_id),
+            "started_before": time::to_rfc3339(started_before),
+        });
+
+        if let Some(started_after) = started_after {
+            body["started_after"] = json!(time::to_rfc3339(started_after));
+        }
+
+        if let Some(if_expr) = if_expr {
+            body["if"] = json!(if_expr);
+        }
+
+        // Unlike run lookups, the created cancellation is returned as the
+        // bare response body rather than wrapped in `data`.
+        let response = self
+            .send(reqwest::Method::POST, "/v1/cancellations", Some(&body))
+            .await?;
+
+        Self::decode(response).await
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+    ) -> Result<T, ClientError> {
+        let response = self.send(method, path, None).await?;
+
+        Self::decode::<ApiEnvelope<T>>(response)
+            .await
+            .map(|envelope| envelope.data)
+    }
+
+    async fn decode<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, ClientError> {
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(ClientError::api(status.as_u16(), body));
+        }
+
+        serde_json::from_str(&body).map_err(|source| ClientError::Decode { body, source })
+    }
+
+    async fn send(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<reqwest::Response, ClientError> {
+        self.client
+            .send_api_request(
+                method,
+                path,
+                body,
+                self.client.signing_key.as_deref(),
+                self.client.signing_key_fallback.as_deref(),
+            )
+            .await
+    }
+}
+
+/// Whether a failed lookup may succeed on a later poll: the event or run is
+/// not visible yet, or the request itself can be retried.
+fn is_pending(err: &ClientError) -> bool {
+    matches!(err, ClientError::Api { status: 404, .. }) || err.is_retryable()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signature::Signature;
+    use crate::utils::test_server::spawn_test_server;
+    use axum::{
+        extract::State,
+        http::{HeaderMap, Method, StatusCode, Uri},
+        routing::{get, post},
+        Router,
+    };
+    use serde_json::json;
+    use std::{
+        collections::VecDeque,
+        sync::{Arc, Mutex},
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    const PRIMARY_SIGNING_KEY: &str =
+        "signkey-test-8ee2262a15e8d3c42d6a840db7af3de2aab08ef632b32a37a687f24b34dba3ff";
+    const FALLBACK_SIGNING_KEY: &str =
+        "signkey-test-1111111111111111111111111111111111111111111111111111111111111111";
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct RequestRecord {
+        authorization: Option<String>,
+        body: Value,
+        method: Method,
+        path: String,
+    }
+
+    #[derive(Clone)]
+    struct ApiState {
+        records: Arc<Mutex<Vec<RequestRecord>>>,
+        responses: Arc<Mutex<VecDeque<(StatusCode, String)>>>,
+    }
+
+    #[tokio::test]
+    async fn get_returns_the_run_with_bearer_auth() {
+        let (origin, records) = spawn_api(vec![(
+            StatusCode::OK,
+            json!({
+                "data": {
+                    "run_id": "run-1",
+                    "status": "Completed",
+                    "output": { "ok": true },
+                    "event_id": "evt-1"
+                }
+            })
+            .to_string(),
+        )]);
+        let client = Inngest::new("test-app")
+            .api_origin(&origin)
+            .signing_key(PRIMARY_SIGNING_KEY);
+
+        let run = client
+            .runs()
+            .get("run-1")
+            .await
+            .expect("run lookup should succeed");
+
+        assert_eq!(run.run_id, "run-1");
+        assert_eq!(run.status, RunStatus::Completed);
+        assert!(run.status.is_finished());
+        assert_eq!(run.output, json!({ "ok": true }));
+        assert_eq!(run.event_id, Some("evt-1".to_string()));
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].method, Method::GET);
+        assert_eq!(records[0].path, "/v1/runs/run-1");
+        assert_eq!(
+            records[0].authorization,
+            Some(format!(
+                "Bearer {}",
+                Signature::new(PRIMARY_SIGNING_KEY)
+                    .hash()
+                    .expect("primary signing key should hash")
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn get_retries_with_fallback_signing_key() {
+        let (origin, records) = spawn_api(vec![
+            (StatusCode::UNAUTHORIZED, "{}".to_string()),
+            (
+                StatusCode::OK,
+                json!({ "data": { "run_id": "run-1", "status": "Running" } }).to_string(),
+            ),
+        ]);
+        let client = Inngest::new("test-app")
+            .api_origin(&origin)
+            .signing_key(PRIMARY_SIGNING_KEY)
+            .signing_key_fallback(FALLBACK_SIGNING_KEY);
+
+        let run = client
+            .runs()
+            .get("run-1")
+            .await
+            .expect("fallback key should be used after a 401");
+
+        assert_eq!(run.status, RunStatus::Running);
+        assert_eq!(run.output, Value::Null);
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[1].authorization,
+            Some(format!(
+                "Bearer {}",
+                Signature::new(FALLBACK_SIGNING_KEY)
+                    .hash()
+                    .expect("fallback signing key should hash")
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn list_for_event_returns_all_runs() {
+        let (origin, records) = spawn_api(vec![(
+            StatusCode::OK,
+            json!({
+                "data": [
+                    { "run_id": "run-1", "status": "Completed" },
+                    { "run_id": "run-2", "status": "Paused" }
+                ]
+            })
+            .to_string(),
+        )]);
+        let client = Inngest::new("test-app").dev(&origin);
+
+        let runs = client
+            .runs()
+            .list_for_event("evt-1")
+            .await
+            .expect("event run lookup should succeed");
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1].run_id, "run-2");
+        assert_eq!(runs[1].status, RunStatus::Unknown);
+        assert_eq!(records.lock().unwrap()[0].path, "/v1/events/evt-1/runs");
+        assert_eq!(records.lock().unwrap()[0].authorization, None);
+    }
+
+    #[tokio::test]
+    async fn wait_for_event_run_polls_through_not_found() {
+        let (origin, records) = spawn_api(vec![
+            (
+                StatusCode::NOT_FOUND,
+                "{\"error\":\"event not found\"}".to_string(),
+            ),
+            (StatusCode::OK, json!({ "data": [] }).to_string()),
+            (
+                StatusCode::OK,
+                json!({ "data": [{ "run_id": "run-1", "status": "Running" }] }).to_string(),
+            ),
+        ]);
+        let client = Inngest::new("test-app").dev(&origin);
+
+        let run = client
+            .runs()
+            .wait_for_event_run("evt-1", Duration::from_secs(5))
+            .await
+            .expect("a 404 should be polled again");
+
+        assert_eq!(run.run_id, "run-1");
+        assert_eq!(records.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn wait_for_completion_retries_not_found_and_server_errors() {
+        let (origin, records) = spawn_api(vec![
+            (StatusCode::NOT_FOUND, "{}".to_string()),
+            (StatusCode::SERVICE_UNAVAILABLE, "{}".to_string()),
+            (
+                StatusCode::OK,
+                json!({ "data": { "run_id": "run-1", "status": "Completed" } }).to_string(),
+            ),
+        ]);
+        let client = Inngest::new("test-app").dev(&origin);
+
+        let run = client
+            .runs()
+            .wait_for_completion("run-1", Duration::from_secs(5))
+            .await
+            .expect("404 and 503 responses should be polled again");
+
+        assert_eq!(run.status, RunStatus::Completed);
+        assert_eq!(records.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn wait_for_completion_fails_fast_on_other_errors() {
+        let (origin, records) = spawn_api(vec![(StatusCode::BAD_REQUEST, "{}".to_string())]);
+        let client = Inngest::new("test-app").dev(&origin);
+
+        let err = client
+            .runs()
+            .wait_for_completion("run-1", Duration::from_secs(5))
+            .await
+            .expect_err("a 400 should not be polled again");
+
+        assert_eq!(err.status(), Some(400));
+        assert_eq!(records.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn cancel_sends_a_delete_request() {
+        let (origin, records) = spawn_api(vec![(StatusCode::OK, "{}".to_string())]);
+        let client = Inngest::new("test-app").dev(&origin);
+
+        client
+            .runs()
+            .cancel("run-1")
+            .await
+            .expect("cancel should succeed");
+
+        let records = records.lock().unwrap();
+        assert_eq!(records[0].method, Method::DELETE);
+        assert_eq!(records[0].path, "/v1/runs/run-1");
+    }
+
+    #[tokio::test]
+    async fn get_surfaces_non_success_statuses() {
+        let (origin, _records) = spawn_api(vec![(
+            StatusCode::NOT_FOUND,
+            "{\"error\":\"run not found\"}".to_string(),
+        )]);
+        let client = Inngest::new("test-app").dev(&origin);
+
+        match client.runs().get("run-1").await {
+            Err(err) => {
+                assert_eq!(err.status(), Some(404));
+                assert!(!err.is_retryable());
+                assert!(matches!(
+                    err,
+                    ClientError::Api { message: Some(ref message), .. } if message == "run not found"
+                ));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn cancel_runs_posts_the_function_range_and_expression() {
+        let (origin, records) = spawn_api(vec![(
+            StatusCode::OK,
+            // The documented response for `POST /v1/cancellations`.
+            json!({
+                "id": "01HMRMPE5ZQ4AMNJ3S2N79QGRZ",
+                "environment_id": "e03843e1-d2df-419e-9b9b-3f0f7e1be4b9",
+                "function_id": "send-email",
+                "started_after": "2023-01-18T00:00:00Z",
+                "started_before": "2023-01-18T23:00:30.001Z",
+                "if": "event.data.user_id == \"user_123\""
+            })
+            .to_string(),
+        )]);
+        let client = Inngest::new("Test App")
+            .api_origin(&origin)
+            .signing_key(PRIMARY_SIGNING_KEY);
+
+        let cancellation = client
+            .cancel_runs(
+                "Send Email",
+                Some(UNIX_EPOCH + Duration::from_secs(1_674_000_000)),
+                UNIX_EPOCH + Duration::from_millis(1_674_082_830_001),
+                Some("event.data.user_id == \"user_123\""),
+            )
+            .await
+            .expect("bulk cancellation should be created");
+
+        assert_eq!(
+            cancellation,
+            Cancellation {
+                id: "01HMRMPE5ZQ4AMNJ3S2N79QGRZ".to_string(),
+                environment_id: Some("e03843e1-d2df-419e-9b9b-3f0f7e1be4b9".to_string()),
+                function_id: Some("send-email".to_string()),
+                started_after: Some("2023-01-18T00:00:00Z".to_string()),
+                started_before: Some("2023-01-18T23:00:30.001Z".to_string()),
+                if_exp: Some("event.data.user_id == \"user_123\"".to_string()),
+            }
+        );
+
+        let records = records.lock().unwrap();
+        assert_eq!(records[0].method, Method::POST);
+        assert_eq!(records[0].path, "/v1/cancellations");
+        assert_eq!(
+            records[0].body,
+            json!({
+                "app_id": "test-app",
+                "function_id": "send-email",
+                "started_after": "2023-01-18T00:00:00.000Z",
+                "started_before": "2023-01-18T23:00:30.001Z",
+                "if": "event.data.user_id == \"user_123\""
+            })
+        );
+    }
+
+    fn spawn_api(responses: Vec<(StatusCode, String)>) -> (String, Arc<Mutex<Vec<RequestRecord>>>) {
+        async fn record(
+            State(state): State<ApiState>,
+            method: Method,
+            headers: HeaderMap,
+            uri: Uri,
+            body: String,
+        ) -> (StatusCode, String) {
+            state.records.lock().unwrap().push(RequestRecord {
+                authorization: headers
+                    .get("authorization")
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_string()),
+                body: serde_json::from_str(&body).unwrap_or(Value::Null),
+                method,
+                path: uri.path().to_string(),
+            });
+
+            state
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .unwrap_or((StatusCode::OK, "{}".to_string()))
+        }
+
+        let records = Arc::new(Mutex::new(Vec::new()));
+        let state = ApiState {
+            records: Arc::clone(&records),
+            responses: Arc::new(Mutex::new(VecDeque::from(responses))),
+        };
+        let app = Router::new()
+            .route("/v1/runs/:run_id", get(record).delete(record))
+            .route("/v1/events/:event_id/runs", get(record))
+            .route("/v1/cancellations", post(record))
+            .with_state(state);
+        let origin = spawn_test_server(app);
+
+        (origin, records)
+    }
+}
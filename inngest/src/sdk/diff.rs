@@ -0,0 +1,258 @@
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+/// The differences between two sync payloads, such as a payload stored from
+/// the last deploy and [`Handler::sync_payload_preview`].
+///
+/// Functions are matched by ID. Every function field other than the ID is
+/// compared, which covers triggers, steps and flow-control settings.
+///
+/// [`Handler::sync_payload_preview`]: crate::handler::Handler::sync_payload_preview
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncDiff {
+    /// A change to the app's serve URL.
+    pub url: Option<FieldChange>,
+    /// IDs of functions only in the new payload.
+    pub added: Vec<String>,
+    /// IDs of functions only in the previous payload.
+    pub removed: Vec<String>,
+    /// Functions present in both payloads whose config changed.
+    pub changed: Vec<FunctionChange>,
+}
+
+/// A function whose synced config differs between two payloads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionChange {
+    pub id: String,
+    pub fields: Vec<FieldChange>,
+}
+
+/// A single field that differs between two payloads. `None` means the field
+/// is not set on that side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+impl SyncDiff {
+    /// Compares a previous sync payload against a new one.
+    pub fn between(previous: &Value, next: &Value) -> Self {
+        let before = functions_by_id(previous);
+        let after = functions_by_id(next);
+
+        let added = after
+            .keys()
+            .filter(|id| !before.contains_key(*id))
+            .cloned()
+            .collect();
+        let removed = before
+            .keys()
+            .filter(|id| !after.contains_key(*id))
+            .cloned()
+            .collect();
+
+        let changed = after
+            .iter()
+            .filter_map(|(id, next_fn)| {
+                let fields = diff_fields(before.get(id)?, next_fn);
+                (!fields.is_empty()).then(|| FunctionChange {
+                    id: id.clone(),
+                    fields,
+                })
+            })
+            .collect();
+
+        let url = field_change("url", previous.get("url"), next.get("url"));
+
+        Self {
+            url,
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    /// Returns `true` when syncing would not change anything.
+    pub fn is_empty(&self) -> bool {
+        self.url.is_none()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+impl Display for SyncDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+
+        if let Some(url) = &self.url {
+            writeln!(f, "{}", url)?;
+        }
+        for id in &self.added {
+            writeln!(f, "+ {}", id)?;
+        }
+        for id in &self.removed {
+            writeln!(f, "- {}", id)?;
+        }
+        for change in &self.changed {
+            writeln!(f, "~ {}", change.id)?;
+            for field in &change.fields {
+                writeln!(f, "    {}", field)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "(unset)".to_string(),
+        };
+
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            show(&self.before),
+            show(&self.after)
+        )
+    }
+}
+
+fn functions_by_id(payload: &Value) -> BTreeMap<String, &Map<String, Value>> {
+    payload
+        .get("functions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|function| {
+            let function = function.as_object()?;
+            let id = function.get("id")?.as_str()?;
+            Some((id.to_string(), function))
+        })
+        .collect()
+}
+
+fn diff_fields(before: &Map<String, Value>, after: &Map<String, Value>) -> Vec<FieldChange> {
+    let mut fields: Vec<&String> = before.keys().chain(after.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter(|field| *field != "id")
+        .filter_map(|field| field_change(field, before.get(field), after.get(field)))
+        .collect()
+}
+
+fn field_change(field: &str, before: Option<&Value>, after: Option<&Value>) -> Option<FieldChange> {
+    let before = before.filter(|value| !value.is_null());
+    let after = after.filter(|value| !value.is_null());
+
+    (before != after).then(|| FieldChange {
+        field: field.to_string(),
+        before: before.cloned(),
+        after: after.cloned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payload(functions: Value) -> Value {
+        json!({
+            "appName": "test-app",
+            "url": "http://127.0.0.1:3000/api/inngest",
+            "functions": functions,
+        })
+    }
+
+    #[test]
+    fn identical_payloads_have_no_changes() {
+        let previous = payload(json!([
+            { "id": "test-app-first", "triggers": [{ "event": "test/first" }] },
+        ]));
+
+        let diff = SyncDiff::between(&previous, &previous.clone());
+
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "no changes\n");
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_functions() {
+        let previous = payload(json!([
+            { "id": "test-app-first", "triggers": [{ "event": "test/first" }] },
+            { "id": "test-app-old", "triggers": [{ "event": "test/old" }] },
+            {
+                "id": "test-app-limited",
+                "triggers": [{ "event": "test/limited" }],
+                "concurrency": 5,
+            },
+        ]));
+        let next = payload(json!([
+            { "id": "test-app-first", "triggers": [{ "event": "test/first.v2" }] },
+            { "id": "test-app-new", "triggers": [{ "event": "test/new" }] },
+            {
+                "id": "test-app-limited",
+                "triggers": [{ "event": "test/limited" }],
+                "throttle": { "limit": 1, "period": "1m" },
+            },
+        ]));
+
+        let diff = SyncDiff::between(&previous, &next);
+
+        assert_eq!(diff.added, vec!["test-app-new".to_string()]);
+        assert_eq!(diff.removed, vec!["test-app-old".to_string()]);
+        assert_eq!(
+            diff.changed,
+            vec![
+                FunctionChange {
+                    id: "test-app-first".to_string(),
+                    fields: vec![FieldChange {
+                        field: "triggers".to_string(),
+                        before: Some(json!([{ "event": "test/first" }])),
+                        after: Some(json!([{ "event": "test/first.v2" }])),
+                    }],
+                },
+                FunctionChange {
+                    id: "test-app-limited".to_string(),
+                    fields: vec![
+                        FieldChange {
+                            field: "concurrency".to_string(),
+                            before: Some(json!(5)),
+                            after: None,
+                        },
+                        FieldChange {
+                            field: "throttle".to_string(),
+                            before: None,
+                            after: Some(json!({ "limit": 1, "period": "1m" })),
+                        },
+                    ],
+                },
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "+ test-app-new\n\
+             - test-app-old\n\
+             ~ test-app-first\n    \
+             triggers: [{\"event\":\"test/first\"}] -> [{\"event\":\"test/first.v2\"}]\n\
+             ~ test-app-limited\n    \
+             concurrency: 5 -> (unset)\n    \
+             throttle: (unset) -> {\"limit\":1,\"period\":\"1m\"}\n"
+        );
+    }
+}
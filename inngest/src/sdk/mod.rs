@@ -1,3 +1,5 @@
+pub mod diff;
+
 use crate::function::Function;
 use crate::version;
 
@@ -166,7 +166,7 @@ mod tests {
             "name": "inngest/scheduled.timer",
             "data": {},
             "user": {},
-            "ts": 1_674_082_830_001 as i64,
+            "ts": 1_674_082_830_001_i64,
             "v": "1"
         })
     }
@@ -16,7 +16,11 @@ use crate::{
     basic_error,
     client::Inngest,
     event::{Event, InngestEvent},
-    result::{DevError, Error, FlowControlError, StepError},
+    expression,
+    function::ServableFn,
+    result::{
+        ClientError, DevError, Error, FlowControlError, InvokeError, NonRetryableError, StepError,
+    },
     utils::duration,
 };
 
@@ -526,6 +530,14 @@ impl Step {
                     "timeout": duration::to_string(opts.timeout),
                 });
                 if let Some(exp) = opts.if_exp {
+                    if let Err(err) = expression::validate_with_roots(&exp, expression::MATCH_ROOTS)
+                    {
+                        return Err(Error::Dev(DevError::NoRetry(NonRetryableError {
+                            message: format!("wait_for_event {} if: {}", id, err),
+                            cause: None,
+                        })));
+                    }
+
                     wait_opts["if"] = json!(&exp);
                 }
 
@@ -550,6 +562,80 @@ impl Step {
         id: &str,
         opts: InvokeFunctionOpts,
     ) -> Result<T, Error> {
+        match self.invoke_step(id, &opts)? {
+            MemoizedStepResult::Data { data } => Ok(data),
+            MemoizedStepResult::Error { error } => Err(Error::Dev(DevError::Step(error))),
+        }
+    }
+
+    /// Invokes a registered function and waits for its typed output.
+    ///
+    /// The function ID is derived from [`ServableFn::slug`], the payload must
+    /// match the target's event type and the result is deserialized into its
+    /// output type. Failures surface as [`DevError::Invoke`], which tells an
+    /// invoke timeout apart from the target function failing.
+    #[must_use = "This method returns a Result type, which should be handled and propagated to the caller"]
+    pub fn invoke_fn<T, E, O>(
+        &self,
+        id: &str,
+        func: &ServableFn<T, E, O>,
+        payload: T,
+    ) -> Result<O, Error>
+    where
+        T: InngestEvent,
+        O: for<'de> Deserialize<'de>,
+    {
+        self.invoke_typed(id, func, payload, None)
+    }
+
+    /// Like [`Step::invoke_fn`], but gives up waiting once `timeout` elapses.
+    #[must_use = "This method returns a Result type, which should be handled and propagated to the caller"]
+    pub fn invoke_fn_with_timeout<T, E, O>(
+        &self,
+        id: &str,
+        func: &ServableFn<T, E, O>,
+        payload: T,
+        timeout: Duration,
+    ) -> Result<O, Error>
+    where
+        T: InngestEvent,
+        O: for<'de> Deserialize<'de>,
+    {
+        self.invoke_typed(id, func, payload, Some(timeout))
+    }
+
+    fn invoke_typed<T, E, O>(
+        &self,
+        id: &str,
+        func: &ServableFn<T, E, O>,
+        payload: T,
+        timeout: Option<Duration>,
+    ) -> Result<O, Error>
+    where
+        T: InngestEvent,
+        O: for<'de> Deserialize<'de>,
+    {
+        let data = serde_json::to_value(&payload)
+            .map_err(|err| basic_error!("error serializing invoke payload: {}", err))?;
+        let opts = InvokeFunctionOpts {
+            function_id: func.slug(),
+            data,
+            timeout,
+        };
+
+        match self.invoke_step(id, &opts)? {
+            MemoizedStepResult::Data { data } => Ok(data),
+            MemoizedStepResult::Error { error } => Err(Error::Dev(DevError::Invoke(Box::new(
+                InvokeError::from_step_error(opts.function_id, error),
+            )))),
+        }
+    }
+
+    fn invoke_step<T: for<'de> Deserialize<'de>>(
+        &self,
+        id: &str,
+        opts: &InvokeFunctionOpts,
+    ) -> Result<MemoizedStepResult<T>, Error> {
         let op = self.new_op(id);
         let hashed = op.hash();
         self.mark_step_seen();
@@ -559,10 +645,7 @@ impl Step {
         }
 
         match self.take_memoized(&hashed) {
-            Some(resp) => match parse_memoized_step_result(resp, "invoke step")? {
-                MemoizedStepResult::Data { data } => Ok(data),
-                MemoizedStepResult::Error { error } => Err(Error::Dev(DevError::Step(error))),
-            },
+            Some(resp) => parse_memoized_step_result(resp, "invoke step"),
 
             None => {
                 self.reject_unrelated_target(&hashed)?;
@@ -705,16 +788,11 @@ impl Display for StepSendEventError {
 
 impl StdError for StepSendEventError {}
 
-impl From<DevError> for StepSendEventError {
-    fn from(err: DevError) -> Self {
-        let message = match err {
-            DevError::Basic(message) => message,
-            DevError::Step(err) => err.to_string(),
-            DevError::RetryAt(err) => err.to_string(),
-            DevError::NoRetry(err) => err.to_string(),
-        };
-
-        Self { message }
+impl From<ClientError> for StepSendEventError {
+    fn from(err: ClientError) -> Self {
+        Self {
+            message: err.to_string(),
+        }
     }
 }
 
@@ -750,14 +828,12 @@ impl Op {
 mod tests {
     use super::*;
     use crate::client::Inngest;
+    use crate::utils::test_server::spawn_test_server;
     use axum::{extract::State, response::IntoResponse, routing::post, Json, Router};
     use serde_json::json;
-    use std::{
-        net::TcpListener,
-        sync::{
-            atomic::{AtomicUsize, Ordering},
-            Arc, Mutex,
-        },
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
     };
 
     #[derive(Clone, Default)]
@@ -1013,6 +1089,30 @@ mod tests {
         assert!(step.genop().is_empty());
     }
 
+    #[test]
+    fn wait_for_event_rejects_invalid_if_expressions() {
+        let client = Inngest::new("test-app");
+        let step = Step::new(client, &HashMap::new(), "step", &[]);
+
+        let result = step.wait_for_event::<TestEventData>(
+            "wait",
+            WaitForEventOpts {
+                event: "test/wait".to_string(),
+                timeout: Duration::from_secs(1),
+                if_exp: Some("event.data.id = async.data.id".to_string()),
+            },
+        );
+
+        match result {
+            Err(Error::Dev(DevError::NoRetry(err))) => assert_eq!(
+                err.message,
+                "wait_for_event wait if: unexpected character `=` at position 15, did you mean `==`?"
+            ),
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(step.genop().is_empty());
+    }
+
     #[tokio::test]
     async fn send_event_emits_step_run_and_reuses_memoized_result() {
         let server = spawn_event_api().await;
@@ -1089,6 +1189,109 @@ mod tests {
         assert!(step.genop().is_empty());
     }
 
+    fn typed_child_fn(client: &Inngest) -> ServableFn<TestEventData, Error, TestEventData> {
+        client.create_function(
+            crate::function::FunctionOpts::new("Child Fn"),
+            crate::function::Trigger::event("test/child"),
+            |input: crate::function::Input<TestEventData>, _step| async move {
+                Ok(input.event.data)
+            },
+        )
+    }
+
+    #[test]
+    fn invoke_fn_plans_op_with_fully_qualified_function_id() {
+        let client = Inngest::new("test-app");
+        let child = typed_child_fn(&client);
+        let step = Step::new(client, &HashMap::new(), "step", &[]);
+
+        let err = step
+            .invoke_fn_with_timeout(
+                "hello",
+                &child,
+                TestEventData {
+                    value: "payload".to_string(),
+                },
+                Duration::from_secs(60),
+            )
+            .expect_err("unmemoized invoke should interrupt");
+        match err {
+            Error::Interrupt(mut flow) => flow.acknowledge(),
+            other => panic!("expected interrupt, got {other:?}"),
+        }
+
+        let ops = step.genop();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(
+            ops[0].opts,
+            json!({
+                "function_id": "test-app-child-fn",
+                "payload": { "data": { "value": "payload" } },
+                "timeout": "1m",
+            })
+        );
+    }
+
+    #[test]
+    fn invoke_fn_returns_typed_memoized_output() {
+        let client = Inngest::new("test-app");
+        let child = typed_child_fn(&client);
+        let state = HashMap::from([(
+            "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d".to_string(),
+            Some(json!({ "data": { "value": "done" } })),
+        )]);
+        let step = Step::new(client, &state, "step", &[]);
+
+        let output = step
+            .invoke_fn(
+                "hello",
+                &child,
+                TestEventData {
+                    value: "ignored".to_string(),
+                },
+            )
+            .expect("memoized invoke should return typed output");
+
+        assert_eq!(
+            output,
+            TestEventData {
+                value: "done".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn invoke_fn_distinguishes_timeouts_from_target_failures() {
+        let client = Inngest::new("test-app");
+        let child = typed_child_fn(&client);
+
+        for (name, timed_out) in [
+            (crate::result::INVOKE_TIMEOUT_ERROR_NAME, true),
+            ("NonRetryableError", false),
+        ] {
+            let state = HashMap::from([(
+                "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d".to_string(),
+                Some(json!({ "error": { "name": name, "message": "boom" } })),
+            )]);
+            let step = Step::new(client.clone(), &state, "step", &[]);
+
+            match step.invoke_fn(
+                "hello",
+                &child,
+                TestEventData {
+                    value: "ignored".to_string(),
+                },
+            ) {
+                Err(Error::Dev(DevError::Invoke(err))) => {
+                    assert_eq!(err.is_timeout(), timed_out);
+                    assert_eq!(err.function_id(), "test-app-child-fn");
+                    assert_eq!(err.step_error().message, "boom");
+                }
+                other => panic!("expected invoke error, got {other:?}"),
+            }
+        }
+    }
+
     #[tokio::test]
     async fn send_events_posts_an_array_payload() {
         let server = spawn_event_api().await;
@@ -1187,23 +1390,11 @@ mod tests {
         }
 
         let state = EventApiState::default();
-        let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
-        let addr = listener.local_addr().expect("listener addr should exist");
         let app = Router::new()
             .route("/e/:event_key", post(ingest))
             .with_state(state.clone());
+        let origin = spawn_test_server(app);
 
-        tokio::spawn(async move {
-            axum::Server::from_tcp(listener)
-                .expect("server should bind")
-                .serve(app.into_make_service())
-                .await
-                .expect("server should serve");
-        });
-
-        TestServer {
-            state,
-            url: format!("http://{}", addr),
-        }
+        TestServer { state, url: origin }
     }
 }
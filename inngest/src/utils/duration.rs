@@ -10,6 +10,45 @@ pub(crate) fn to_string(dur: Duration) -> String {
     dur_str("", dur)
 }
 
+/// Parses an Inngest time string such as `30s`, `1h30m` or `7d`.
+///
+/// Returns `None` for values that are not plain durations, such as
+/// expressions or timestamps.
+pub(crate) fn parse(value: &str) -> Option<Duration> {
+    let mut total = Duration::ZERO;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let amount: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let nanos_per_unit: u128 = match &rest[..unit_len] {
+            "ns" => 1,
+            "us" | "µs" => 1000,
+            "ms" => MILLISECOND_IN_NANO,
+            "s" => SECOND_IN_NANO,
+            "m" => MINUTE_IN_NANO,
+            "h" => HOUR_IN_NANO,
+            "d" => DAY_IN_NANO,
+            "w" => 7 * DAY_IN_NANO,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+
+        let nanos = u64::try_from(amount as u128 * nanos_per_unit).ok()?;
+        total = total.checked_add(Duration::from_nanos(nanos))?;
+    }
+
+    Some(total)
+}
+
 fn dur_str(acc: &str, dur: Duration) -> String {
     let ns = dur.as_nanos();
 
@@ -149,6 +188,26 @@ mod tests {
         assert_eq!("7d", d);
     }
 
+    #[test]
+    fn parse_round_trips_to_string() {
+        for secs in [1, 45, 90, 3600, 5400, 86_400, 7 * 86_400 + 20] {
+            let dur = Duration::from_secs(secs);
+            assert_eq!(parse(&to_string(dur)), Some(dur));
+        }
+
+        assert_eq!(parse("2w"), Some(Duration::from_secs(14 * 86_400)));
+        assert_eq!(parse("250ms"), Some(Duration::from_millis(250)));
+    }
+
+    #[test]
+    fn parse_rejects_non_durations() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("10"), None);
+        assert_eq!(parse("10x"), None);
+        assert_eq!(parse("event.data.timeout"), None);
+        assert_eq!(parse("2024-01-01T00:00:00Z"), None);
+    }
+
     #[test]
     fn to_string_day_s() {
         let day_in_sec = 60 * 60 * 24;
@@ -1,2 +1,4 @@
 pub(crate) mod duration;
+#[cfg(test)]
+pub(crate) mod test_server;
 pub(crate) mod time;
@@ -0,0 +1,20 @@
+use std::net::TcpListener;
+
+use axum::Router;
+
+/// Serves `app` on a free local port in the background and returns its
+/// origin, such as `http://127.0.0.1:40123`.
+pub(crate) fn spawn_test_server(app: Router) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("listener should bind");
+    let addr = listener.local_addr().expect("listener addr should exist");
+
+    tokio::spawn(async move {
+        axum::Server::from_tcp(listener)
+            .expect("server should bind")
+            .serve(app.into_make_service())
+            .await
+            .expect("server should serve");
+    });
+
+    format!("http://{}", addr)
+}
@@ -16,3 +16,66 @@ pub(crate) fn now() -> i64 {
         Err(_) => 0,
     }
 }
+
+/// Formats a system time as an RFC 3339 UTC timestamp with millisecond
+/// precision, e.g. `2024-01-02T03:04:05.006Z`.
+pub(crate) fn to_rfc3339(time: SystemTime) -> String {
+    let ms = match time.duration_since(std::time::UNIX_EPOCH) {
+        Ok(dur) => dur.as_millis() as i64,
+        Err(err) => -(err.duration().as_millis() as i64),
+    };
+
+    let days = ms.div_euclid(86_400_000);
+    let ms_of_day = ms.rem_euclid(86_400_000);
+    let (year, month, day) = civil_from_days(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        (ms_of_day / 60_000) % 60,
+        (ms_of_day / 1000) % 60,
+        ms_of_day % 1000
+    )
+}
+
+// Converts days since the unix epoch into a proleptic Gregorian date.
+// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn to_rfc3339_formats_the_unix_epoch() {
+        assert_eq!(to_rfc3339(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn to_rfc3339_formats_millisecond_timestamps() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_674_082_830_001);
+        assert_eq!(to_rfc3339(time), "2023-01-18T23:00:30.001Z");
+    }
+
+    #[test]
+    fn to_rfc3339_handles_leap_days() {
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_164_800);
+        assert_eq!(to_rfc3339(time), "2024-02-29T00:00:00.000Z");
+    }
+}
@@ -0,0 +1,643 @@
+//! Receives third-party webhooks and re-emits them as Inngest events.
+//!
+//! Each route declares how the webhook is authenticated and how the raw
+//! request is turned into events:
+//!
+//! ```ignore
+//! let webhooks = WebhookRouter::new(&client)
+//!     .route(
+//!         "/webhooks/github",
+//!         WebhookVerifier::github(&github_secret),
+//!         |headers, body| {
+//!             let payload: Value = serde_json::from_slice(&body).unwrap_or_default();
+//!             let kind = headers
+//!                 .get("x-github-event")
+//!                 .and_then(|v| v.to_str().ok())
+//!                 .unwrap_or("unknown");
+//!             vec![Event::new(&format!("github/{kind}"), payload)]
+//!         },
+//!     )
+//!     .into_router();
+//!
+//! let app = Router::new().merge(webhooks);
+//! ```
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use hmac::{Hmac, Mac};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::{client::Inngest, event::Event, result::ClientError, utils::time};
+
+type HmacSha256 = Hmac<Sha256>;
+type VerifyFn = dyn Fn(&HeaderMap, &[u8]) -> bool + Send + Sync + 'static;
+type TransformFn = dyn Fn(HeaderMap, Bytes) -> Vec<Event<Value>> + Send + Sync + 'static;
+
+const STRIPE_SIGNATURE_HEADER: &str = "stripe-signature";
+const GITHUB_SIGNATURE_HEADER: &str = "x-hub-signature-256";
+const DEFAULT_STRIPE_TOLERANCE: Duration = Duration::from_secs(300);
+const DEFAULT_RETRIES: u32 = 3;
+const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(100);
+/// Keeps retries well under the ~10 second timeout of GitHub, Stripe and
+/// similar providers, which redeliver when a request takes too long.
+const DEFAULT_RETRY_DEADLINE: Duration = Duration::from_secs(3);
+/// Headers carrying a unique ID per delivery, which providers repeat on
+/// redelivery: GitHub's and the Standard Webhooks one.
+const DELIVERY_ID_HEADERS: [&str; 2] = ["x-github-delivery", "webhook-id"];
+
+/// Authenticates an incoming webhook request before it is transformed.
+#[derive(Clone)]
+pub enum WebhookVerifier {
+    /// Accepts every request. Only use this behind another layer of auth.
+    None,
+    /// A hex-encoded HMAC-SHA256 of the raw body carried in `header`, with an
+    /// optional prefix such as `sha256=`.
+    HmacSha256Hex {
+        header: String,
+        secret: String,
+        prefix: Option<String>,
+    },
+    /// Stripe's `t=<timestamp>,v1=<signature>` scheme, signing
+    /// `<timestamp>.<body>` and rejecting timestamps outside `tolerance`.
+    Stripe { secret: String, tolerance: Duration },
+    /// Any other scheme, given the request headers and raw body.
+    Custom(Arc<VerifyFn>),
+}
+
+impl WebhookVerifier {
+    /// Verifies a hex-encoded HMAC-SHA256 signature in the given header.
+    pub fn hmac_sha256_hex(header: &str, secret: &str) -> Self {
+        Self::HmacSha256Hex {
+            header: header.to_lowercase(),
+            secret: secret.to_string(),
+            prefix: None,
+        }
+    }
+
+    /// Verifies GitHub's `X-Hub-Signature-256: sha256=<hex>` header.
+    pub fn github(secret: &str) -> Self {
+        Self::HmacSha256Hex {
+            header: GITHUB_SIGNATURE_HEADER.to_string(),
+            secret: secret.to_string(),
+            prefix: Some("sha256=".to_string()),
+        }
+    }
+
+    /// Verifies Stripe's `Stripe-Signature` header with a five minute
+    /// tolerance.
+    pub fn stripe(secret: &str) -> Self {
+        Self::Stripe {
+            secret: secret.to_string(),
+            tolerance: DEFAULT_STRIPE_TOLERANCE,
+        }
+    }
+
+    /// Verifies requests with a custom function.
+    pub fn custom(verify: impl Fn(&HeaderMap, &[u8]) -> bool + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(verify))
+    }
+
+    /// Returns `true` if the request carries a valid signature.
+    pub fn verify(&self, headers: &HeaderMap, body: &[u8]) -> bool {
+        match self {
+            Self::None => true,
+            Self::HmacSha256Hex {
+                header,
+                secret,
+                prefix,
+            } => {
+                let Some(value) = header_str(headers, header) else {
+                    return false;
+                };
+                let signature = match prefix {
+                    Some(prefix) => match value.strip_prefix(prefix.as_str()) {
+                        Some(signature) => signature,
+                        None => return false,
+                    },
+                    None => value,
+                };
+
+                verify_hex_hmac(secret, body, signature)
+            }
+            Self::Stripe { secret, tolerance } => {
+                let Some(value) = header_str(headers, STRIPE_SIGNATURE_HEADER) else {
+                    return false;
+                };
+
+                let mut timestamp = None;
+                let mut signatures = Vec::new();
+                for part in value.split(',') {
+                    match part.trim().split_once('=') {
+                        Some(("t", ts)) => timestamp = ts.parse::<i64>().ok(),
+                        Some(("v1", sig)) => signatures.push(sig),
+                        _ => {}
+                    }
+                }
+
+                let Some(timestamp) = timestamp else {
+                    return false;
+                };
+                if (time::now() - timestamp).unsigned_abs() > tolerance.as_secs() {
+                    return false;
+                }
+
+                let mut signed = format!("{}.", timestamp).into_bytes();
+                signed.extend_from_slice(body);
+                signatures
+                    .into_iter()
+                    .any(|signature| verify_hex_hmac(secret, &signed, signature))
+            }
+            Self::Custom(verify) => verify(headers, body),
+        }
+    }
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+fn verify_hex_hmac(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Ok(expected) = base16::decode(signature.trim().as_bytes()) else {
+        return false;
+    };
+    let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+struct WebhookRoute {
+    path: String,
+    verifier: WebhookVerifier,
+    transform: Box<TransformFn>,
+}
+
+/// Builds an axum router that turns third-party webhooks into Inngest
+/// events.
+///
+/// Requests failing verification are rejected with `401`. Events produced by
+/// a route's transform are sent with [`Inngest::send_events`], retrying
+/// retryable failures with exponential backoff before answering `502` so the
+/// webhook provider redelivers.
+///
+/// Events without an `id` are given one derived from the delivery ID header
+/// (`X-GitHub-Delivery` or `webhook-id`), or else a hash of the body, so that
+/// Inngest deduplicates retries and provider redeliveries.
+pub struct WebhookRouter {
+    client: Inngest,
+    routes: Vec<WebhookRoute>,
+    retries: u32,
+    retry_backoff: Duration,
+    retry_deadline: Duration,
+}
+
+impl WebhookRouter {
+    /// Creates an empty webhook router that sends events with `client`.
+    pub fn new(client: &Inngest) -> Self {
+        Self {
+            client: client.clone(),
+            routes: Vec::new(),
+            retries: DEFAULT_RETRIES,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+            retry_deadline: DEFAULT_RETRY_DEADLINE,
+        }
+    }
+
+    /// Adds a `POST` route that verifies requests and transforms them into
+    /// events.
+    pub fn route(
+        mut self,
+        path: &str,
+        verifier: WebhookVerifier,
+        transform: impl Fn(HeaderMap, Bytes) -> Vec<Event<Value>> + Send + Sync + 'static,
+    ) -> Self {
+        self.routes.push(WebhookRoute {
+            path: path.to_string(),
+            verifier,
+            transform: Box::new(transform),
+        });
+        self
+    }
+
+    /// Overrides how many times a failed event send is retried.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Overrides the delay before the first retry; later retries double it.
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Overrides the total time spent sending a webhook's events, including
+    /// retries, before answering `502`. Defaults to 3 seconds.
+    pub fn retry_deadline(mut self, deadline: Duration) -> Self {
+        self.retry_deadline = deadline;
+        self
+    }
+
+    /// Converts the configured routes into an axum router.
+    pub fn into_router<S>(self) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let mut router = Router::new();
+
+        for route in self.routes {
+            let path = route.path.clone();
+            let route = Arc::new(route);
+            let client = self.client.clone();
+            let retry = RetryPolicy {
+                retries: self.retries,
+                backoff: self.retry_backoff,
+                deadline: self.retry_deadline,
+            };
+
+            router = router.route(
+                &path,
+                post(move |headers: HeaderMap, body: Bytes| {
+                    let route = Arc::clone(&route);
+                    let client = client.clone();
+
+                    async move {
+                        if !route.verifier.verify(&headers, &body) {
+                            return (
+                                StatusCode::UNAUTHORIZED,
+                                Json(json!({ "error": "invalid webhook signature" })),
+                            );
+                        }
+
+                        let key = delivery_key(&route.path, &headers, &body);
+                        let mut events = (route.transform)(headers, body);
+                        assign_event_ids(&mut events, &key);
+
+                        match send_with_retries(&client, &events, &retry).await {
+                            Ok(ids) => (StatusCode::OK, Json(json!({ "ids": ids }))),
+                            Err(err) => {
+                                let status = if err.is_retryable() {
+                                    StatusCode::BAD_GATEWAY
+                                } else {
+                                    StatusCode::INTERNAL_SERVER_ERROR
+                                };
+
+                                (status, Json(json!({ "error": err.to_string() })))
+                            }
+                        }
+                    }
+                }),
+            );
+        }
+
+        router
+    }
+}
+
+#[derive(Clone, Copy)]
+struct RetryPolicy {
+    retries: u32,
+    backoff: Duration,
+    deadline: Duration,
+}
+
+/// A stable key for one webhook delivery, shared by its redeliveries.
+fn delivery_key(path: &str, headers: &HeaderMap, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(path.as_bytes());
+    hasher.update([0]);
+    match DELIVERY_ID_HEADERS
+        .iter()
+        .find_map(|name| header_str(headers, name))
+    {
+        Some(delivery_id) => hasher.update(delivery_id.as_bytes()),
+        None => hasher.update(body),
+    }
+
+    base16::encode_lower(&hasher.finalize())
+}
+
+/// Gives each event without an ID a deterministic one, so Inngest drops
+/// duplicates when the same delivery is sent more than once.
+fn assign_event_ids(events: &mut [Event<Value>], key: &str) {
+    for (index, event) in events.iter_mut().enumerate() {
+        if event.id.is_none() {
+            event.id = Some(format!("{}-{}", key, index));
+        }
+    }
+}
+
+async fn send_with_retries(
+    client: &Inngest,
+    events: &[Event<Value>],
+    retry: &RetryPolicy,
+) -> Result<Vec<String>, ClientError> {
+    if events.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let deadline = Instant::now() + retry.deadline;
+    let events: Vec<&Event<Value>> = events.iter().collect();
+    let mut attempt = 0;
+    loop {
+        let result =
+            match tokio::time::timeout_at(deadline.into(), client.send_events(&events)).await {
+                Ok(result) => result,
+                Err(_) => {
+                    return Err(ClientError::Timeout(format!(
+                        "sending webhook events took longer than {:?}",
+                        retry.deadline
+                    )))
+                }
+            };
+
+        match result {
+            Ok(response) => return Ok(response.ids),
+            Err(err) if err.is_retryable() && attempt < retry.retries => {
+                let delay = retry.backoff * 2u32.saturating_pow(attempt);
+                if Instant::now() + delay >= deadline {
+                    return Err(err);
+                }
+
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_server::spawn_test_server;
+    use axum::extract::State;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    const SECRET: &str = "webhook-secret";
+
+    #[derive(Clone, Default)]
+    struct EventApiState {
+        bodies: Arc<Mutex<Vec<Value>>>,
+        failures: Arc<AtomicUsize>,
+    }
+
+    #[test]
+    fn github_verifier_checks_prefixed_hex_signatures() {
+        let verifier = WebhookVerifier::github(SECRET);
+        let body = b"{\"action\":\"opened\"}";
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            GITHUB_SIGNATURE_HEADER,
+            format!("sha256={}", sign(SECRET, body)).parse().unwrap(),
+        );
+        assert!(verifier.verify(&headers, body));
+        assert!(!verifier.verify(&headers, b"{\"action\":\"closed\"}"));
+
+        headers.insert(GITHUB_SIGNATURE_HEADER, sign(SECRET, body).parse().unwrap());
+        assert!(!verifier.verify(&headers, body));
+        assert!(!verifier.verify(&HeaderMap::new(), body));
+    }
+
+    #[test]
+    fn stripe_verifier_checks_timestamped_signatures() {
+        let verifier = WebhookVerifier::stripe(SECRET);
+        let body = b"{\"type\":\"invoice.paid\"}";
+        let now = time::now();
+
+        let mut headers = HeaderMap::new();
+        let signed = [format!("{now}.").as_bytes(), body].concat();
+        headers.insert(
+            STRIPE_SIGNATURE_HEADER,
+            format!("t={now},v1=deadbeef,v1={}", sign(SECRET, &signed))
+                .parse()
+                .unwrap(),
+        );
+        assert!(verifier.verify(&headers, body));
+
+        let stale = now - 600;
+        let signed = [format!("{stale}.").as_bytes(), body].concat();
+        headers.insert(
+            STRIPE_SIGNATURE_HEADER,
+            format!("t={stale},v1={}", sign(SECRET, &signed))
+                .parse()
+                .unwrap(),
+        );
+        assert!(!verifier.verify(&headers, body));
+    }
+
+    #[tokio::test]
+    async fn router_forwards_transformed_events_after_retrying() {
+        let (event_origin, state) = spawn_event_api(1);
+        let client = Inngest::new("test-app")
+            .event_api_origin(&event_origin)
+            .event_key("test-key");
+        let origin = spawn_webhooks(
+            WebhookRouter::new(&client)
+                .retry_backoff(Duration::from_millis(1))
+                .route(
+                    "/webhooks/github",
+                    WebhookVerifier::github(SECRET),
+                    |headers, body| {
+                        let kind = headers
+                            .get("x-github-event")
+                            .and_then(|value| value.to_str().ok())
+                            .unwrap_or("unknown")
+                            .to_string();
+                        let payload: Value = serde_json::from_slice(&body).unwrap();
+
+                        vec![Event::new(&format!("github/{kind}"), payload)]
+                    },
+                ),
+        );
+
+        let body = "{\"action\":\"opened\"}";
+        let response = reqwest::Client::new()
+            .post(format!("{origin}/webhooks/github"))
+            .header("x-github-event", "pull_request")
+            .header(
+                GITHUB_SIGNATURE_HEADER,
+                format!("sha256={}", sign(SECRET, body.as_bytes())),
+            )
+            .body(body)
+            .send()
+            .await
+            .expect("webhook request should complete");
+
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(
+            response.json::<Value>().await.unwrap(),
+            json!({ "ids": ["evt-1"] })
+        );
+
+        let bodies = state.bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[1][0]["name"], "github/pull_request");
+        assert_eq!(bodies[1][0]["data"], json!({ "action": "opened" }));
+        assert!(bodies[0][0]["id"].is_string());
+        assert_eq!(bodies[0][0]["id"], bodies[1][0]["id"]);
+    }
+
+    #[test]
+    fn event_ids_are_derived_from_the_delivery() {
+        let mut headers = HeaderMap::new();
+        let by_body = delivery_key("/webhooks/github", &headers, b"{}");
+        assert_eq!(by_body, delivery_key("/webhooks/github", &headers, b"{}"));
+        assert_ne!(by_body, delivery_key("/webhooks/github", &headers, b"[]"));
+        assert_ne!(by_body, delivery_key("/webhooks/stripe", &headers, b"{}"));
+
+        headers.insert("x-github-delivery", "delivery-1".parse().unwrap());
+        let by_header = delivery_key("/webhooks/github", &headers, b"{}");
+        assert_ne!(by_header, by_body);
+        assert_eq!(by_header, delivery_key("/webhooks/github", &headers, b"[]"));
+
+        let mut events = vec![
+            Event::new("github/push", json!({})),
+            Event::new("github/push", json!({})),
+        ];
+        events[1].id = Some("custom".to_string());
+        assign_event_ids(&mut events, &by_header);
+
+        assert_eq!(events[0].id, Some(format!("{by_header}-0")));
+        assert_eq!(events[1].id, Some("custom".to_string()));
+    }
+
+    #[tokio::test]
+    async fn router_rejects_invalid_signatures_without_sending() {
+        let (event_origin, state) = spawn_event_api(0);
+        let client = Inngest::new("test-app")
+            .event_api_origin(&event_origin)
+            .event_key("test-key");
+        let origin = spawn_webhooks(WebhookRouter::new(&client).route(
+            "/webhooks/github",
+            WebhookVerifier::github(SECRET),
+            |_headers, _body| vec![Event::new("github/push", json!({}))],
+        ));
+
+        let response = reqwest::Client::new()
+            .post(format!("{origin}/webhooks/github"))
+            .header(GITHUB_SIGNATURE_HEADER, "sha256=00")
+            .body("{}")
+            .send()
+            .await
+            .expect("webhook request should complete");
+
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+        assert!(state.bodies.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn router_returns_bad_gateway_once_retries_are_exhausted() {
+        let (event_origin, state) = spawn_event_api(usize::MAX);
+        let client = Inngest::new("test-app")
+            .event_api_origin(&event_origin)
+            .event_key("test-key");
+        let origin = spawn_webhooks(
+            WebhookRouter::new(&client)
+                .retries(2)
+                .retry_backoff(Duration::from_millis(1))
+                .route("/webhooks/any", WebhookVerifier::None, |_headers, _body| {
+                    vec![Event::new("webhook/received", json!({}))]
+                }),
+        );
+
+        let response = reqwest::Client::new()
+            .post(format!("{origin}/webhooks/any"))
+            .body("{}")
+            .send()
+            .await
+            .expect("webhook request should complete");
+
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_GATEWAY);
+        assert_eq!(state.bodies.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn router_stops_retrying_at_the_deadline() {
+        let (event_origin, state) = spawn_event_api(usize::MAX);
+        let client = Inngest::new("test-app")
+            .event_api_origin(&event_origin)
+            .event_key("test-key");
+        let origin = spawn_webhooks(
+            WebhookRouter::new(&client)
+                .retries(100)
+                .retry_backoff(Duration::from_millis(50))
+                .retry_deadline(Duration::from_millis(300))
+                .route("/webhooks/any", WebhookVerifier::None, |_headers, _body| {
+                    vec![Event::new("webhook/received", json!({}))]
+                }),
+        );
+
+        let started = Instant::now();
+        let response = reqwest::Client::new()
+            .post(format!("{origin}/webhooks/any"))
+            .body("{}")
+            .send()
+            .await
+            .expect("webhook request should complete");
+
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_GATEWAY);
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(state.bodies.lock().unwrap().len() < 5);
+    }
+
+    fn sign(secret: &str, body: &[u8]) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(body);
+        base16::encode_lower(&mac.finalize().into_bytes())
+    }
+
+    fn spawn_webhooks(webhooks: WebhookRouter) -> String {
+        spawn_test_server(webhooks.into_router())
+    }
+
+    fn spawn_event_api(failures: usize) -> (String, EventApiState) {
+        async fn ingest(
+            State(state): State<EventApiState>,
+            Json(body): Json<Value>,
+        ) -> (StatusCode, Json<Value>) {
+            state.bodies.lock().unwrap().push(body);
+
+            let failures = state.failures.load(Ordering::SeqCst);
+            if failures > 0 {
+                state.failures.store(failures - 1, Ordering::SeqCst);
+                return (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({ "error": "unavailable", "status": 503 })),
+                );
+            }
+
+            (
+                StatusCode::OK,
+                Json(json!({ "ids": ["evt-1"], "status": 200 })),
+            )
+        }
+
+        let state = EventApiState {
+            bodies: Arc::new(Mutex::new(Vec::new())),
+            failures: Arc::new(AtomicUsize::new(failures)),
+        };
+        let app = Router::new()
+            .route("/e/:event_key", post(ingest))
+            .with_state(state.clone());
+        let origin = spawn_test_server(app);
+
+        (origin, state)
+    }
+}
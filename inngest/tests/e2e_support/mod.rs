@@ -1,8 +1,7 @@
 #![allow(dead_code)]
 
 use axum::Router;
-use inngest::{client::Inngest, handler::Handler, serve};
-use serde::Deserialize;
+use inngest::{client::Inngest, handler::Handler, result::ClientError, serve};
 use serde_json::Value;
 use std::{
     fs, io,
@@ -15,21 +14,15 @@ use std::{
 
 pub const DEV_SERVER_ORIGIN: &str = "http://127.0.0.1:8288";
 
-#[derive(Debug, Deserialize)]
-struct ApiEnvelope<T> {
-    data: T,
-}
-
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct EventRunRecord {
     pub run_id: String,
     pub status: String,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct RunRecord {
     pub status: String,
-    #[serde(default)]
     pub output: Value,
 }
 
@@ -142,7 +135,9 @@ pub async fn spawn_app(client: Inngest, funcs: Vec<inngest::handler::RegisteredF
     let base_url = format!("http://127.0.0.1:{}", addr.port());
 
     let mut handler = Handler::new(&client).serve_origin(&base_url);
-    handler.register_fns(funcs);
+    handler
+        .register_fns(funcs)
+        .expect("functions should register");
 
     let app = Router::new()
         .route(
@@ -255,54 +250,36 @@ async fn wait_for_http_ok(origin: &str, timeout: Duration) {
     }
 }
 
-async fn fetch_run(run_id: &str) -> Option<RunRecord> {
+pub async fn fetch_run(run_id: &str) -> Option<RunRecord> {
     // Missing runs are normal while the event is still being ingested.
-    let response = reqwest::Client::new()
-        .get(format!("{DEV_SERVER_ORIGIN}/v1/runs/{run_id}"))
-        .send()
-        .await
-        .expect("run lookup should complete");
-
-    if response.status() == reqwest::StatusCode::NOT_FOUND {
-        return None;
-    }
-
-    assert!(
-        response.status().is_success(),
-        "run lookup failed with status {}",
-        response.status()
-    );
-
-    let envelope = response
-        .json::<ApiEnvelope<RunRecord>>()
-        .await
-        .expect("run lookup should decode");
-
-    Some(envelope.data)
+    let run = match dev_server_client().runs().get(run_id).await {
+        Ok(run) => run,
+        Err(ClientError::Api { status: 404, .. }) => return None,
+        Err(err) => panic!("run lookup failed: {err}"),
+    };
+
+    Some(RunRecord {
+        status: run.status.to_string(),
+        output: run.output,
+    })
 }
 
 async fn fetch_event_runs(event_id: &str) -> Vec<EventRunRecord> {
     // Child event runs appear only after the durable event has been dispatched.
-    let response = reqwest::Client::new()
-        .get(format!("{DEV_SERVER_ORIGIN}/v1/events/{event_id}/runs"))
-        .send()
-        .await
-        .expect("event run lookup should complete");
-
-    if response.status() == reqwest::StatusCode::NOT_FOUND {
-        return Vec::new();
-    }
-
-    assert!(
-        response.status().is_success(),
-        "event run lookup failed with status {}",
-        response.status()
-    );
-
-    let envelope = response
-        .json::<ApiEnvelope<Vec<EventRunRecord>>>()
-        .await
-        .expect("event run lookup should decode");
+    let runs = match dev_server_client().runs().list_for_event(event_id).await {
+        Ok(runs) => runs,
+        Err(ClientError::Api { status: 404, .. }) => return Vec::new(),
+        Err(err) => panic!("event run lookup failed: {err}"),
+    };
+
+    runs.into_iter()
+        .map(|run| EventRunRecord {
+            run_id: run.run_id,
+            status: run.status.to_string(),
+        })
+        .collect()
+}
 
-    envelope.data
+fn dev_server_client() -> Inngest {
+    Inngest::new("e2e-support").dev(DEV_SERVER_ORIGIN)
 }
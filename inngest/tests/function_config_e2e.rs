@@ -21,7 +21,7 @@ use std::{
         atomic::{AtomicUsize, Ordering},
         Arc, Mutex,
     },
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -74,11 +74,6 @@ struct PrioritizedEventData {
     priority: i32,
 }
 
-#[derive(Debug, Deserialize)]
-struct ApiEnvelope<T> {
-    data: T,
-}
-
 async fn wait_for_value<T: Clone>(
     state: &Arc<Mutex<T>>,
     timeout: Duration,
@@ -100,34 +95,6 @@ async fn wait_for_value<T: Clone>(
     }
 }
 
-async fn fetch_run_record(run_id: &str) -> Option<e2e_support::RunRecord> {
-    let response = reqwest::Client::new()
-        .get(format!(
-            "{}/v1/runs/{run_id}",
-            e2e_support::DEV_SERVER_ORIGIN
-        ))
-        .send()
-        .await
-        .expect("run lookup should complete");
-
-    if response.status() == reqwest::StatusCode::NOT_FOUND {
-        return None;
-    }
-
-    assert!(
-        response.status().is_success(),
-        "run lookup failed with status {}",
-        response.status()
-    );
-
-    let envelope = response
-        .json::<ApiEnvelope<e2e_support::RunRecord>>()
-        .await
-        .expect("run lookup should decode");
-
-    Some(envelope.data)
-}
-
 async fn wait_for_run_status_matching(
     run_id: &str,
     timeout: Duration,
@@ -137,7 +104,7 @@ async fn wait_for_run_status_matching(
     let mut last_status = None::<String>;
 
     loop {
-        if let Some(run) = fetch_run_record(run_id).await {
+        if let Some(run) = e2e_support::fetch_run(run_id).await {
             if predicate(&run.status) {
                 return run;
             }
@@ -836,6 +803,81 @@ async fn cancel_stops_an_in_flight_run_when_a_matching_event_arrives() {
     assert_eq!(completed_after_sleep.load(Ordering::SeqCst), 0);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn cancel_runs_cancels_in_flight_runs_matching_the_expression() {
+    let _lock = DevServerLock::acquire();
+    let _dev_server = DevServer::start().await;
+
+    let app_name = e2e_support::unique_name("function-config-bulk-cancel-app");
+    let event_name = e2e_support::unique_name("test.function.bulk.cancel");
+
+    let client = Inngest::new(&app_name).dev(e2e_support::DEV_SERVER_ORIGIN);
+    let run_id_state = Arc::new(Mutex::new(None::<String>));
+    let completed_after_sleep = Arc::new(AtomicUsize::new(0));
+
+    let run_id_capture = Arc::clone(&run_id_state);
+    let completed_after_sleep_capture = Arc::clone(&completed_after_sleep);
+    let func: ServableFn<KeyedEventData, Error> = client.create_function(
+        FunctionOpts::new("bulk-cancel-parent").name("Bulk Cancel Parent"),
+        Trigger::event(&event_name),
+        move |input: Input<KeyedEventData>, step: StepTool| {
+            let run_id_capture = Arc::clone(&run_id_capture);
+            let completed_after_sleep_capture = Arc::clone(&completed_after_sleep_capture);
+
+            async move {
+                if run_id_capture.lock().unwrap().is_none() {
+                    *run_id_capture.lock().unwrap() = Some(input.ctx.run_id.clone());
+                }
+
+                step.sleep("hold-bulk-cancel", Duration::from_secs(10))?;
+                completed_after_sleep_capture.fetch_add(1, Ordering::SeqCst);
+
+                Ok(json!({ "message": input.event.data.message }))
+            }
+        },
+    );
+
+    let app = spawn_app(client.clone(), vec![func.into()]).await;
+    app.sync().await;
+
+    client
+        .send_event(&Event::new(
+            &event_name,
+            KeyedEventData {
+                key: "group-1".to_string(),
+                message: "first".to_string(),
+            },
+        ))
+        .await
+        .expect("start event should send successfully");
+
+    let run_id = wait_for_state(&run_id_state, Duration::from_secs(5)).await;
+    wait_for_run_status(&run_id, "Running", Duration::from_secs(5)).await;
+
+    let cancellation = client
+        .cancel_runs(
+            "bulk-cancel-parent",
+            None,
+            SystemTime::now(),
+            Some("event.data.key == 'group-1'"),
+        )
+        .await
+        .expect("bulk cancellation should be created");
+    assert!(!cancellation.id.is_empty());
+    assert_eq!(
+        cancellation.if_exp.as_deref(),
+        Some("event.data.key == 'group-1'")
+    );
+
+    let run = wait_for_run_status_matching(&run_id, Duration::from_secs(15), |status| {
+        status.to_ascii_lowercase().contains("cancel")
+    })
+    .await;
+
+    assert!(run.status.to_ascii_lowercase().contains("cancel"));
+    assert_eq!(completed_after_sleep.load(Ordering::SeqCst), 0);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn concurrency_limit_serializes_step_execution_across_runs() {
     let _lock = DevServerLock::acquire();